@@ -0,0 +1,421 @@
+// src/archetypes.rs - Типы врагов, описанные данными в assets/enemies/*.enemy.ron
+use std::collections::HashMap;
+
+use bevy::asset::LoadedFolder;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::console::{Console, ConsoleAppExt, ConsoleCommand};
+use crate::crowd::CrowdAgent;
+use crate::data::RonAssetLoader;
+use crate::enemies::{Enemy, EnemyAttack, Health};
+use crate::navigation::NavAgent;
+use crate::perception::Perception;
+use crate::weapons::{Faction, Weapon, WeaponAim};
+use crate::melee::{ExplodeAttack, MeleeAttack};
+use crate::models::CharacterModel;
+use crate::player::Player;
+use crate::stats::Stats;
+use crate::state::InGame;
+
+pub struct ArchetypesPlugin;
+impl Plugin for ArchetypesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EnemyArchetype>()
+           .register_asset_loader(RonAssetLoader::<EnemyArchetype>::new(&["enemy.ron"]))
+           .register_console_command("spawn", "spawn <type> - spawn an enemy in front of the player")
+           .add_systems(Startup, load_archetypes)
+           .add_systems(Update, (index_archetypes, spawn_command));
+    }
+}
+
+/// Описание типа врага
+#[derive(Asset, TypePath, Deserialize, Clone)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub shape: EnemyShape,
+    /// Коллайдер, если он отличается от формы меша
+    #[serde(default)]
+    pub collider: Option<EnemyShape>,
+    pub material: EnemyMaterial,
+    /// glTF-модель вместо меша фигуры
+    #[serde(default)]
+    pub model: Option<CharacterModel>,
+    pub speed: f32,
+    pub health: f32,
+    /// Очки за убийство
+    #[serde(default = "default_score")]
+    pub score: u32,
+    pub attack: AttackDefinition,
+    #[serde(default)]
+    pub ai: AiDefinition,
+    /// Что может выпасть при смерти: предметы из assets/pickups
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum EnemyShape {
+    Cuboid { size: [f32; 3] },
+    Sphere { radius: f32 },
+    Capsule { radius: f32, length: f32 },
+    Cylinder { radius: f32, height: f32 },
+}
+
+impl EnemyShape {
+    fn mesh(&self) -> Mesh {
+        match *self {
+            EnemyShape::Cuboid { size } => Cuboid::new(size[0], size[1], size[2]).into(),
+            EnemyShape::Sphere { radius } => Sphere::new(radius).into(),
+            EnemyShape::Capsule { radius, length } => Capsule3d::new(radius, length).into(),
+            EnemyShape::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match *self {
+            EnemyShape::Cuboid { size } => Collider::cuboid(size[0] * 0.5, size[1] * 0.5, size[2] * 0.5),
+            EnemyShape::Sphere { radius } => Collider::ball(radius),
+            EnemyShape::Capsule { radius, length } => Collider::capsule_y(length * 0.5, radius),
+            EnemyShape::Cylinder { radius, height } => Collider::cylinder(height * 0.5, radius),
+        }
+    }
+
+    /// Радиус в горизонтальной плоскости - для толпы и навигации
+    fn horizontal_radius(&self) -> f32 {
+        match *self {
+            EnemyShape::Cuboid { size } => Vec2::new(size[0], size[2]).length() * 0.5,
+            EnemyShape::Sphere { radius } => radius,
+            EnemyShape::Capsule { radius, .. } => radius,
+            EnemyShape::Cylinder { radius, .. } => radius,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EnemyMaterial {
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub emissive: [f32; 3],
+    #[serde(default)]
+    pub metallic: f32,
+    #[serde(default = "default_roughness")]
+    pub roughness: f32,
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+fn default_score() -> u32 {
+    100
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum AttackKind {
+    Melee,
+    Ranged,
+    Explode,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct AttackDefinition {
+    pub kind: AttackKind,
+    pub damage: f32,
+    pub range: f32,
+    pub cooldown: f32,
+    /// Скорость снаряда для атаки `Ranged`
+    #[serde(default = "default_projectile_speed")]
+    pub projectile_speed: f32,
+    /// Фазы удара для атаки `Melee` (сек): замах, активные кадры, восстановление
+    #[serde(default = "default_windup")]
+    pub windup: f32,
+    #[serde(default = "default_active")]
+    pub active: f32,
+    #[serde(default = "default_recovery")]
+    pub recovery: f32,
+}
+
+fn default_projectile_speed() -> f32 {
+    18.0
+}
+
+fn default_windup() -> f32 {
+    0.45
+}
+
+fn default_active() -> f32 {
+    0.15
+}
+
+fn default_recovery() -> f32 {
+    0.35
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct AiDefinition {
+    pub view_distance: f32,
+    pub fov_degrees: f32,
+    pub hearing_multiplier: f32,
+    pub memory_duration: f32,
+    pub repath_interval: f32,
+}
+
+impl Default for AiDefinition {
+    fn default() -> Self {
+        let perception = Perception::default();
+        Self {
+            view_distance: perception.view_distance,
+            fov_degrees: perception.fov.to_degrees(),
+            hearing_multiplier: perception.hearing_multiplier,
+            memory_duration: perception.memory_duration,
+            repath_interval: NavAgent::default().repath_interval,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LootEntry {
+    pub item: String,
+    pub chance: f32,
+}
+
+/// Загруженные типы врагов по имени
+#[derive(Resource)]
+pub struct EnemyArchetypes {
+    folder: Handle<LoadedFolder>,
+    by_name: HashMap<String, Handle<EnemyArchetype>>,
+    // Меш и материал создаются один раз на тип, а не на каждого врага
+    visuals: HashMap<String, (Handle<Mesh>, Handle<StandardMaterial>)>,
+}
+
+impl EnemyArchetypes {
+    pub fn is_ready(&self) -> bool {
+        !self.by_name.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.by_name.contains_key(name)
+    }
+
+    /// Имена загруженных типов по алфавиту
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.by_name.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn get<'a>(&self, name: &str, assets: &'a Assets<EnemyArchetype>) -> Option<&'a EnemyArchetype> {
+        self.by_name.get(name).and_then(|handle| assets.get(handle))
+    }
+}
+
+// Консольный `spawn` ставит врага на таком расстоянии перед игроком
+const SPAWN_COMMAND_DISTANCE: f32 = 4.0;
+
+/// Тип, из которого создан враг
+#[derive(Component)]
+pub struct ArchetypeName(pub String);
+
+fn load_archetypes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyArchetypes {
+        folder: asset_server.load_folder("enemies"),
+        by_name: HashMap::new(),
+        visuals: HashMap::new(),
+    });
+}
+
+fn index_archetypes(
+    mut archetypes: ResMut<EnemyArchetypes>,
+    mut folder_events: EventReader<AssetEvent<LoadedFolder>>,
+    folders: Res<Assets<LoadedFolder>>,
+    enemy_assets: Res<Assets<EnemyArchetype>>,
+) {
+    for event in folder_events.read() {
+        if !event.is_loaded_with_dependencies(&archetypes.folder) {
+            continue;
+        }
+        let Some(folder) = folders.get(&archetypes.folder) else {
+            continue;
+        };
+        let mut by_name = HashMap::new();
+        for handle in &folder.handles {
+            let Ok(handle) = handle.clone().try_typed::<EnemyArchetype>() else {
+                continue;
+            };
+            if let Some(archetype) = enemy_assets.get(&handle) {
+                by_name.insert(archetype.name.clone(), handle);
+            }
+        }
+        info!("Loaded {} enemy archetypes", by_name.len());
+        archetypes.by_name = by_name;
+        archetypes.visuals.clear();
+    }
+}
+
+pub trait EnemyCommandsExt {
+    /// Спавнит врага заданного типа. Возвращает команды сущности, чтобы вызывающий
+    /// мог добавить свои компоненты (они применятся поверх компонентов типа).
+    /// Если тип неизвестен, сущность удаляется - перед добавлением своих
+    /// компонентов проверяйте `EnemyArchetypes::contains`
+    fn spawn_enemy_archetype(&mut self, name: &str, position: Vec3) -> EntityCommands<'_>;
+}
+
+impl EnemyCommandsExt for Commands<'_, '_> {
+    fn spawn_enemy_archetype(&mut self, name: &str, position: Vec3) -> EntityCommands<'_> {
+        let name = name.to_string();
+        let mut entity = self.spawn_empty();
+        entity.queue(move |entity: Entity, world: &mut World| {
+            spawn_enemy(world, entity, &name, position);
+        });
+        entity
+    }
+}
+
+fn spawn_enemy(world: &mut World, entity: Entity, name: &str, position: Vec3) {
+    let archetype = world.get_resource::<EnemyArchetypes>()
+        .and_then(|archetypes| archetypes.by_name.get(name))
+        .and_then(|handle| world.resource::<Assets<EnemyArchetype>>().get(handle))
+        .cloned();
+    let Some(archetype) = archetype else {
+        warn!("Unknown enemy archetype '{}'", name);
+        world.despawn(entity);
+        return;
+    };
+
+    let cached = world.resource::<EnemyArchetypes>().visuals.get(name).cloned();
+    let (mesh, material) = cached.unwrap_or_else(|| {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(archetype.shape.mesh());
+        let [r, g, b] = archetype.material.color;
+        let [er, eg, eb] = archetype.material.emissive;
+        let material = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::srgb(r, g, b),
+            emissive: LinearRgba::rgb(er, eg, eb),
+            metallic: archetype.material.metallic,
+            perceptual_roughness: archetype.material.roughness,
+            ..default()
+        });
+        world.resource_mut::<EnemyArchetypes>()
+            .visuals
+            .insert(name.to_string(), (mesh.clone(), material.clone()));
+        (mesh, material)
+    });
+
+    let collider_shape = archetype.collider.unwrap_or(archetype.shape);
+    let radius = collider_shape.horizontal_radius();
+    // Враги дальнего боя держат дистанцию, остальные подходят вплотную
+    let surround_distance = match archetype.attack.kind {
+        AttackKind::Ranged => archetype.attack.range * 0.8,
+        AttackKind::Melee | AttackKind::Explode => (archetype.attack.range * 0.8).max(radius + 0.6),
+    };
+
+    let model = archetype.model.as_ref().map(|model| model.bundle(world.resource::<AssetServer>()));
+
+    let mut nav_agent = NavAgent::default();
+    nav_agent.repath_interval = archetype.ai.repath_interval;
+
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    entity.insert((
+        // Визуал
+        Mesh3d(mesh),
+        MeshMaterial3d(material),
+        Transform::from_translation(position),
+
+        // Физика
+        RigidBody::Dynamic,
+        Velocity::default(),
+        collider_shape.collider(),
+        LockedAxes::ROTATION_LOCKED, // Не позволяем врагу вращаться
+
+        // Компоненты врага
+        Enemy {
+            speed: archetype.speed,
+            target: None,
+        },
+        EnemyAttack(archetype.attack.clone()),
+        Health::new(archetype.health),
+        nav_agent,
+        Perception {
+            view_distance: archetype.ai.view_distance,
+            fov: archetype.ai.fov_degrees.to_radians(),
+            hearing_multiplier: archetype.ai.hearing_multiplier,
+            memory_duration: archetype.ai.memory_duration,
+            ..default()
+        },
+        CrowdAgent {
+            radius,
+            surround_distance,
+            surround_radius: surround_distance + 4.0,
+            ..default()
+        },
+        (Faction::Enemy, ArchetypeName(archetype.name.clone()), Stats::default(), StateScoped(InGame)),
+        Name::new(format!("Enemy ({})", archetype.name)),
+    ));
+
+    if let Some(model) = model {
+        entity.insert(Visibility::Hidden);
+        entity.with_children(|parent| {
+            parent.spawn(model);
+        });
+    }
+
+    let attack = &archetype.attack;
+    if attack.kind == AttackKind::Melee {
+        entity.insert(MeleeAttack::new(
+            attack.damage,
+            attack.range,
+            attack.windup,
+            attack.active,
+            attack.recovery,
+            attack.cooldown,
+        ));
+    }
+    if attack.kind == AttackKind::Explode {
+        entity.insert(ExplodeAttack { damage: attack.damage, range: attack.range });
+    }
+    if attack.kind == AttackKind::Ranged {
+        entity.insert((
+            Weapon {
+                name: archetype.name.clone(),
+                damage: archetype.attack.damage,
+                fire_rate: 1.0 / archetype.attack.cooldown.max(0.05),
+                last_shot_time: 0.0,
+                noise_radius: 0.0, // Враги не реагируют на выстрелы друг друга
+                projectile_speed: archetype.attack.projectile_speed,
+                ammo: None,
+                ..default()
+            },
+            WeaponAim::default(),
+        ));
+    }
+}
+
+fn spawn_command(
+    mut commands: Commands,
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    archetypes: Res<EnemyArchetypes>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    for command in console_commands.read().filter(|command| command.name == "spawn") {
+        let Some(name) = command.args.first() else {
+            console.print(format!("Enemy types: {}", archetypes.names().join(", ")));
+            continue;
+        };
+        if !archetypes.contains(name) {
+            console.print(format!("No such enemy type '{}'", name));
+            continue;
+        }
+        let Ok(player) = player_query.get_single() else {
+            console.print("No level is running");
+            continue;
+        };
+        let forward = (player.rotation * Vec3::NEG_Z).with_y(0.0).normalize_or(Vec3::NEG_Z);
+        commands.spawn_enemy_archetype(name, player.translation + forward * SPAWN_COMMAND_DISTANCE);
+        console.print(format!("Spawned '{}'", name));
+    }
+}
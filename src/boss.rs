@@ -0,0 +1,495 @@
+// src/boss.rs - Боссы: фазы по порогам здоровья, сценарные атаки и запертая арена
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::archetypes::{EnemyArchetypes, EnemyCommandsExt};
+use crate::crowd::{CrowdAgent, CrowdSet};
+use crate::data::RonAssetLoader;
+use crate::enemies::{Health, Hit};
+use crate::stats::{ModifierOp, ModifierSource, Stacking, Stat, StatModifier, Stats};
+use crate::melee::MeleeAttack;
+use crate::navigation::NavMesh;
+use crate::perception::{Perception, PerceptionSet};
+use crate::player::Player;
+use crate::weapons::{spawn_bullet, Faction};
+use crate::state::{GameState, InGame};
+
+pub struct BossPlugin;
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BossDefinition>()
+           .register_asset_loader(RonAssetLoader::<BossDefinition>::new(&["boss.ron"]))
+           .add_event::<BossPhaseChanged>()
+           .add_event::<BossDefeated>()
+           .add_systems(Update, (
+               update_boss_arenas,
+               update_boss_phases,
+               run_boss_attacks,
+               log_boss_events,
+           ).chain().after(PerceptionSet).after(CrowdSet::Avoid).run_if(in_state(GameState::Playing)))
+           .add_systems(Update, (
+               invalidate_navmesh_on_walls,
+               draw_arena_triggers,
+           ));
+    }
+}
+
+// Пули кольца летят ниже центра босса - на уровне пояса игрока
+const RING_HEIGHT: f32 = -0.8;
+// Миньоны появляются на таком расстоянии от босса
+const SUMMON_DISTANCE: f32 = 3.0;
+
+/// Описание босса из assets/bosses/*.boss.ron
+#[derive(Asset, TypePath, Deserialize)]
+pub struct BossDefinition {
+    /// Имя на полоске здоровья
+    pub name: String,
+    /// Тип врага из assets/enemies: тело, здоровье, скорость и ближняя атака
+    pub archetype: String,
+    /// Фазы по убыванию порога здоровья, первая действует с начала боя
+    pub phases: Vec<BossPhase>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct BossPhase {
+    /// Фаза начинается, когда доля здоровья опустилась до этого значения
+    pub health_threshold: f32,
+    #[serde(default = "default_speed_multiplier")]
+    pub speed_multiplier: f32,
+    /// Пауза между атаками (сек)
+    pub attack_interval: f32,
+    /// Свечение босса в этой фазе
+    #[serde(default)]
+    pub emissive: [f32; 3],
+    /// Атаки выполняются по кругу в заданном порядке
+    pub attacks: Vec<BossAttack>,
+}
+
+fn default_speed_multiplier() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub enum BossAttack {
+    /// Кольцо пуль во все стороны
+    ProjectileRing { count: u32, speed: f32, damage: f32 },
+    /// Рывок в сторону игрока после заметного замаха
+    Charge { windup: f32, duration: f32, speed: f32, damage: f32 },
+    /// Призыв миньонов заданного типа вокруг босса
+    Summon { enemy: String, count: u32 },
+}
+
+#[derive(Event)]
+pub struct BossPhaseChanged {
+    pub boss: Entity,
+    pub phase: usize,
+}
+
+#[derive(Event)]
+pub struct BossDefeated {
+    pub name: String,
+}
+
+enum BossAction {
+    Idle,
+    Charge {
+        windup: f32,
+        remaining: f32,
+        speed: f32,
+        damage: f32,
+        direction: Vec3,
+        hit: bool,
+    },
+}
+
+/// Босс поверх обычного врага: ИИ движения и ближний бой берутся из его типа
+#[derive(Component)]
+pub struct Boss {
+    pub name: String,
+    pub phase: usize,
+    phases: Vec<BossPhase>,
+    applied_phase: Option<usize>, // Фаза, чьи скорость и свечение уже выставлены
+    /// Свой материал босса - свечение фазы не должно попасть на других врагов его типа
+    material: Option<Handle<StandardMaterial>>,
+    /// Свечение текущей фазы
+    pub emissive: LinearRgba,
+    attack_timer: f32,
+    next_attack: usize,
+    action: BossAction,
+}
+
+impl Boss {
+    fn new(definition: &BossDefinition) -> Self {
+        Self {
+            name: definition.name.clone(),
+            phase: 0,
+            phases: definition.phases.clone(),
+            applied_phase: None,
+            material: None,
+            emissive: LinearRgba::BLACK,
+            attack_timer: definition.phases.first().map_or(0.0, |phase| phase.attack_interval),
+            next_attack: 0,
+            action: BossAction::Idle,
+        }
+    }
+
+    /// Самая поздняя фаза, порог которой уже пройден
+    fn phase_for(&self, health_fraction: f32) -> usize {
+        self.phases
+            .iter()
+            .rposition(|phase| health_fraction <= phase.health_threshold)
+            .unwrap_or(0)
+    }
+}
+
+enum ArenaState {
+    Waiting,
+    Locked { boss: Entity, walls: Vec<Entity> },
+    Defeated,
+}
+
+/// Арена босса: когда игрок заходит внутрь квадрата, арена запирается стенами
+/// и появляется босс. Стены исчезают после его смерти
+#[derive(Component)]
+pub struct BossArenaTrigger {
+    pub definition: Handle<BossDefinition>,
+    pub half_extent: f32,
+    state: ArenaState,
+}
+
+impl BossArenaTrigger {
+    pub fn new(definition: Handle<BossDefinition>, half_extent: f32) -> Self {
+        Self {
+            definition,
+            half_extent,
+            state: ArenaState::Waiting,
+        }
+    }
+
+    /// Точка внутри запертой арены - туда не должны попадать враги волн
+    pub fn locks_in(&self, center: Vec3, point: Vec3) -> bool {
+        matches!(self.state, ArenaState::Locked { .. })
+            && (point.x - center.x).abs() < self.half_extent
+            && (point.z - center.z).abs() < self.half_extent
+    }
+}
+
+/// Стена запертой арены
+#[derive(Component)]
+pub struct ArenaWall;
+
+fn update_boss_arenas(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    definitions: Res<Assets<BossDefinition>>,
+    archetypes: Res<EnemyArchetypes>,
+    player_query: Query<&Transform, With<Player>>,
+    bosses: Query<(), With<Boss>>,
+    mut triggers: Query<(&Transform, &mut BossArenaTrigger)>,
+    mut defeated: EventWriter<BossDefeated>,
+) {
+    let player_position = player_query.get_single().ok().map(|transform| transform.translation);
+
+    for (transform, mut trigger) in triggers.iter_mut() {
+        let center = transform.translation;
+        match &trigger.state {
+            ArenaState::Waiting => {
+                let Some(player_position) = player_position else {
+                    continue;
+                };
+                // Срабатываем с запасом от края, чтобы стена не появилась в игроке
+                let inner = trigger.half_extent - 1.0;
+                let inside = (player_position.x - center.x).abs() < inner
+                    && (player_position.z - center.z).abs() < inner;
+                if !inside {
+                    continue;
+                }
+                let Some(definition) = definitions.get(&trigger.definition) else {
+                    continue;
+                };
+                if !archetypes.contains(&definition.archetype) {
+                    continue;
+                }
+
+                // Босс появляется у дальней от игрока стороны арены
+                let away = Vec3::new(center.x - player_position.x, 0.0, center.z - player_position.z)
+                    .normalize_or(Vec3::NEG_Z);
+                let spawn_position = Vec3::new(center.x, 1.5, center.z) + away * trigger.half_extent * 0.6;
+                let boss = commands
+                    .spawn_enemy_archetype(&definition.archetype, spawn_position)
+                    .insert(Boss::new(definition))
+                    .queue(move |mut entity: EntityWorldMut| {
+                        // Босс сразу знает, где игрок
+                        if let Some(mut perception) = entity.get_mut::<Perception>() {
+                            perception.last_known_position = Some(player_position);
+                        }
+                        // Материал типа общий - заводим боссу свою копию
+                        let Some(shared) = entity.get::<MeshMaterial3d<StandardMaterial>>().map(|material| material.0.clone()) else {
+                            return;
+                        };
+                        let own = entity.world_scope(|world| {
+                            let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+                            let copy = materials.get(&shared).cloned()?;
+                            Some(materials.add(copy))
+                        });
+                        if let Some(own) = own {
+                            entity.insert(MeshMaterial3d(own.clone()));
+                            if let Some(mut boss) = entity.get_mut::<Boss>() {
+                                boss.material = Some(own);
+                            }
+                        }
+                    })
+                    .id();
+
+                let walls = spawn_arena_walls(&mut commands, &mut meshes, &mut materials, center, trigger.half_extent);
+                info!("Boss encounter started: {}", definition.name);
+                trigger.state = ArenaState::Locked { boss, walls };
+            }
+            ArenaState::Locked { boss, walls } => {
+                if bosses.contains(*boss) {
+                    continue;
+                }
+                for wall in walls {
+                    commands.entity(*wall).despawn();
+                }
+                if let Some(definition) = definitions.get(&trigger.definition) {
+                    defeated.send(BossDefeated { name: definition.name.clone() });
+                }
+                trigger.state = ArenaState::Defeated;
+            }
+            ArenaState::Defeated => {}
+        }
+    }
+}
+
+fn spawn_arena_walls(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    center: Vec3,
+    half_extent: f32,
+) -> Vec<Entity> {
+    const HEIGHT: f32 = 3.0;
+    const THICKNESS: f32 = 0.5;
+
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(1.0, 0.2, 0.1, 0.35),
+        emissive: LinearRgba::rgb(1.5, 0.2, 0.1),
+        alpha_mode: AlphaMode::Blend,
+        ..default()
+    });
+    let length = half_extent * 2.0 + THICKNESS;
+    let sides = [
+        (Vec3::new(0.0, 0.0, -half_extent), Vec3::new(length, HEIGHT, THICKNESS)),
+        (Vec3::new(0.0, 0.0, half_extent), Vec3::new(length, HEIGHT, THICKNESS)),
+        (Vec3::new(-half_extent, 0.0, 0.0), Vec3::new(THICKNESS, HEIGHT, length)),
+        (Vec3::new(half_extent, 0.0, 0.0), Vec3::new(THICKNESS, HEIGHT, length)),
+    ];
+
+    sides.into_iter().map(|(offset, size)| {
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::from_size(size))),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(Vec3::new(center.x, HEIGHT * 0.5, center.z) + offset),
+            RigidBody::Fixed,
+            Collider::cuboid(size.x * 0.5, size.y * 0.5, size.z * 0.5),
+            ArenaWall,
+            Name::new("Arena Wall"),
+            StateScoped(InGame),
+        )).id()
+    }).collect()
+}
+
+/// Переход между фазами по порогам здоровья
+fn update_boss_phases(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut bosses: Query<(Entity, &mut Boss, &mut Stats, &Health)>,
+    mut phase_changed: EventWriter<BossPhaseChanged>,
+) {
+    for (entity, mut boss, mut stats, health) in bosses.iter_mut() {
+        let phase_index = boss.phase_for(health.fraction());
+        if boss.applied_phase == Some(phase_index) {
+            continue;
+        }
+        let Some(phase) = boss.phases.get(phase_index).cloned() else {
+            continue;
+        };
+
+        stats.add(
+            StatModifier::new(Stat::MoveSpeed, ModifierOp::Multiply(phase.speed_multiplier), ModifierSource::BossPhase),
+            Stacking::Replace,
+        );
+        let [r, g, b] = phase.emissive;
+        boss.emissive = LinearRgba::rgb(r, g, b);
+        // Пишем в свой материал, а не в текущий: во время замаха на теле временная копия
+        if let Some(material) = boss.material.as_ref().and_then(|material| materials.get_mut(material)) {
+            material.emissive = boss.emissive;
+        }
+        if boss.applied_phase.is_some() {
+            // Новая фаза начинается со своей первой атаки, почти без паузы
+            boss.next_attack = 0;
+            boss.attack_timer = boss.attack_timer.min(1.0);
+            phase_changed.send(BossPhaseChanged { boss: entity, phase: phase_index });
+        }
+        boss.phase = phase_index;
+        boss.applied_phase = Some(phase_index);
+    }
+}
+
+fn run_boss_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    archetypes: Res<EnemyArchetypes>,
+    mut player_query: Query<(&Transform, &mut Health), (With<Player>, Without<Boss>)>,
+    mut bosses: Query<(Entity, &Transform, &mut Boss, &Perception, &CrowdAgent, Option<&MeleeAttack>, &mut Velocity, &Stats)>,
+) {
+    let Ok((player_transform, mut player_health)) = player_query.get_single_mut() else {
+        return;
+    };
+    let player_position = player_transform.translation;
+    let dt = time.delta_secs();
+
+    for (entity, transform, mut boss, perception, crowd, melee, mut velocity, stats) in bosses.iter_mut() {
+        let boss = &mut *boss;
+        let position = transform.translation;
+        let to_player = Vec3::new(player_position.x - position.x, 0.0, player_position.z - position.z);
+
+        // Рывок перекрывает скорость, выставленную ИИ и толпой
+        if let BossAction::Charge { windup, remaining, speed, damage, direction, hit } = &mut boss.action {
+            if *windup > 0.0 {
+                // Во время замаха стоим и доворачиваемся на игрока
+                *windup -= dt;
+                *direction = to_player.normalize_or(*direction);
+                velocity.linvel = Vec3::ZERO;
+            } else {
+                *remaining -= dt;
+                velocity.linvel = *direction * *speed;
+                if !*hit && to_player.length() < crowd.radius + 0.8 {
+                    player_health.take_damage(*damage, Hit {
+                        source: Some(entity),
+                        impulse: *direction * *speed * 0.5,
+                    });
+                    *hit = true;
+                }
+            }
+            if *remaining <= 0.0 {
+                boss.action = BossAction::Idle;
+            }
+            continue;
+        }
+
+        if perception.last_known_position.is_none() || melee.is_some_and(MeleeAttack::is_attacking) {
+            continue;
+        }
+        boss.attack_timer -= dt;
+        if boss.attack_timer > 0.0 {
+            continue;
+        }
+        let Some(phase) = boss.phases.get(boss.phase) else {
+            continue;
+        };
+        boss.attack_timer = phase.attack_interval;
+        if phase.attacks.is_empty() {
+            continue;
+        }
+        let attack = phase.attacks[boss.next_attack % phase.attacks.len()].clone();
+        boss.next_attack += 1;
+
+        match attack {
+            BossAttack::ProjectileRing { count, speed, damage } => {
+                // Каждое следующее кольцо повернуто, чтобы нельзя было стоять в одном просвете
+                let offset = boss.next_attack as f32 * 0.37;
+                for i in 0..count {
+                    let angle = offset + TAU * i as f32 / count as f32;
+                    let direction = Vec3::new(angle.cos(), 0.0, angle.sin());
+                    spawn_bullet(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        Vec3::new(position.x, position.y + RING_HEIGHT, position.z) + direction * (crowd.radius + 0.5),
+                        direction * speed,
+                        stats.apply(Stat::Damage, damage),
+                        Faction::Enemy,
+                        Some(entity),
+                    );
+                }
+            }
+            BossAttack::Charge { windup, duration, speed, damage } => {
+                boss.action = BossAction::Charge {
+                    windup,
+                    remaining: duration,
+                    speed,
+                    damage: stats.apply(Stat::Damage, damage),
+                    direction: to_player.normalize_or(Vec3::NEG_Z),
+                    hit: false,
+                };
+            }
+            BossAttack::Summon { enemy, count } => {
+                if !archetypes.contains(&enemy) {
+                    warn!("Boss {}: unknown minion type '{}'", boss.name, enemy);
+                    continue;
+                }
+                for i in 0..count {
+                    let angle = TAU * i as f32 / count.max(1) as f32;
+                    let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * SUMMON_DISTANCE;
+                    commands
+                        .spawn_enemy_archetype(&enemy, position + offset)
+                        .queue(move |mut minion: EntityWorldMut| {
+                            if let Some(mut perception) = minion.get_mut::<Perception>() {
+                                perception.last_known_position = Some(player_position);
+                            }
+                        });
+                }
+            }
+        }
+    }
+}
+
+/// Стены арены - статичная геометрия, навигационную сетку нужно перезапечь
+fn invalidate_navmesh_on_walls(
+    mut navmesh: ResMut<NavMesh>,
+    added: Query<(), Added<ArenaWall>>,
+    mut removed: RemovedComponents<ArenaWall>,
+) {
+    if !added.is_empty() || removed.read().next().is_some() {
+        navmesh.dirty = true;
+    }
+}
+
+/// Контур еще не запертой арены на полу
+fn draw_arena_triggers(
+    mut gizmos: Gizmos,
+    triggers: Query<(&Transform, &BossArenaTrigger)>,
+) {
+    for (transform, trigger) in triggers.iter() {
+        if !matches!(trigger.state, ArenaState::Waiting) {
+            continue;
+        }
+        let center = Vec3::new(transform.translation.x, 0.15, transform.translation.z);
+        gizmos.rect(
+            Isometry3d::new(center, Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2)),
+            Vec2::splat(trigger.half_extent * 2.0),
+            Color::srgb(1.0, 0.3, 0.1),
+        );
+    }
+}
+
+fn log_boss_events(
+    bosses: Query<&Boss>,
+    mut phase_changed: EventReader<BossPhaseChanged>,
+    mut defeated: EventReader<BossDefeated>,
+) {
+    for event in phase_changed.read() {
+        if let Ok(boss) = bosses.get(event.boss) {
+            info!("{} enters phase {}", boss.name, event.phase + 1);
+        }
+    }
+    for event in defeated.read() {
+        info!("Boss defeated: {}", event.name);
+    }
+}
@@ -0,0 +1,194 @@
+// src/console.rs - Консоль разработчика: открывается клавишей ` (тильда).
+// Модули регистрируют свои команды через `register_console_command` и читают
+// событие `ConsoleCommand`; пока консоль открыта, игра не видит клавиатуру и мышь
+use std::collections::BTreeMap;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+pub struct ConsolePlugin;
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+           .add_event::<ConsoleCommand>()
+           .add_systems(Startup, spawn_console)
+           .add_systems(PreUpdate, block_game_input.after(InputSystem))
+           .add_systems(Update, (
+               read_console_input,
+               update_console_view.run_if(resource_changed::<Console>),
+           ).chain());
+    }
+}
+
+// Сколько строк вывода хранится и сколько видно
+const MAX_LINES: usize = 200;
+const VISIBLE_LINES: usize = 14;
+
+/// Введенная команда: первое слово - имя, остальные - аргументы
+#[derive(Event)]
+pub struct ConsoleCommand {
+    pub name: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Resource, Default)]
+pub struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,
+    history: Vec<String>,
+    /// Зарегистрированные команды и их описание для `help`
+    commands: BTreeMap<&'static str, &'static str>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        if self.lines.len() > MAX_LINES {
+            self.lines.remove(0);
+        }
+    }
+
+    fn submit(&mut self, commands: &mut EventWriter<ConsoleCommand>) {
+        let line = std::mem::take(&mut self.input);
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.print(format!("> {}", line));
+        self.history.push(line.to_string());
+
+        let mut words = line.split_whitespace().map(str::to_string);
+        let Some(name) = words.next() else {
+            return;
+        };
+        match name.as_str() {
+            "help" => {
+                let help: Vec<String> = self.commands.values().map(|help| format!("  {}", help)).collect();
+                self.print("Commands:");
+                for line in help {
+                    self.print(line);
+                }
+            }
+            "clear" => self.lines.clear(),
+            _ if self.commands.contains_key(name.as_str()) => {
+                commands.send(ConsoleCommand { name, args: words.collect() });
+            }
+            _ => self.print(format!("Unknown command '{}', type 'help'", name)),
+        }
+    }
+}
+
+pub trait ConsoleAppExt {
+    /// Добавляет команду в консоль; `help` - строка для списка команд
+    fn register_console_command(&mut self, name: &'static str, help: &'static str) -> &mut Self;
+}
+
+impl ConsoleAppExt for App {
+    fn register_console_command(&mut self, name: &'static str, help: &'static str) -> &mut Self {
+        self.init_resource::<Console>();
+        self.world_mut().resource_mut::<Console>().commands.insert(name, help);
+        self
+    }
+}
+
+#[derive(Component)]
+struct ConsoleView;
+
+#[derive(Component)]
+struct ConsoleText;
+
+fn spawn_console(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(40.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                padding: UiRect::all(Val::Px(8.0)),
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            // Поверх HUD и меню
+            GlobalZIndex(100),
+            ConsoleView,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.9, 0.8)),
+                ConsoleText,
+            ));
+        });
+}
+
+/// Нажатия, набранные в консоли, не должны управлять игрой и меню
+fn block_game_input(
+    console: Res<Console>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+) {
+    if console.open {
+        keyboard.reset_all();
+        mouse.reset_all();
+    }
+}
+
+fn read_console_input(
+    mut keys: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    for event in keys.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => console.submit(&mut commands),
+            Key::Escape => console.open = false,
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::ArrowUp => {
+                if let Some(last) = console.history.last().cloned() {
+                    console.input = last;
+                }
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => console.input.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+fn update_console_view(
+    console: Res<Console>,
+    mut views: Query<&mut Node, With<ConsoleView>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    for mut node in views.iter_mut() {
+        node.display = if console.open { Display::Flex } else { Display::None };
+    }
+    let start = console.lines.len().saturating_sub(VISIBLE_LINES);
+    let mut lines = console.lines[start..].to_vec();
+    lines.push(format!("] {}_", console.input));
+    for mut text in texts.iter_mut() {
+        text.0 = lines.join("\n");
+    }
+}
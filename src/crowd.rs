@@ -0,0 +1,257 @@
+// src/crowd.rs - Локальное избегание столкновений и окружение цели толпой врагов
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::player::{self, Player};
+use crate::enemies::Enemy;
+use crate::perception::{Perception, PerceptionSet};
+use crate::state::GameState;
+use crate::stats::{Stat, Stats};
+use crate::world::Launched;
+
+pub struct CrowdPlugin;
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpatialHash::new(2.0))
+           .add_systems(Update, (
+               rebuild_spatial_hash,
+               assign_surround_slots,
+           ).chain().after(PerceptionSet).in_set(CrowdSet::Plan).run_if(in_state(GameState::Playing)))
+           .add_systems(Update, apply_crowd_avoidance.in_set(CrowdSet::Avoid).run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Планирование (слоты окружения) идет до ИИ, избегание - после того,
+/// как ИИ выставил желаемую скорость в `Velocity`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CrowdSet {
+    Plan,
+    Avoid,
+}
+
+/// Пространственный хэш по горизонтальной плоскости для быстрого поиска соседей
+#[derive(Resource)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec3)>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, position: Vec3) -> IVec2 {
+        IVec2::new(
+            (position.x / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    pub fn clear(&mut self) {
+        // Сохраняем выделенную память под списки между кадрами
+        for entries in self.cells.values_mut() {
+            entries.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        let cell = self.cell(position);
+        self.cells.entry(cell).or_default().push((entity, position));
+    }
+
+    /// Все сущности в радиусе `radius` от точки (по горизонтали)
+    pub fn neighbors(&self, position: Vec3, radius: f32) -> impl Iterator<Item = (Entity, Vec3)> + '_ {
+        let min = self.cell(position - Vec3::new(radius, 0.0, radius));
+        let max = self.cell(position + Vec3::new(radius, 0.0, radius));
+        let radius_squared = radius * radius;
+        (min.y..=max.y)
+            .flat_map(move |z| (min.x..=max.x).map(move |x| IVec2::new(x, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(move |(_, other)| {
+                let offset = Vec2::new(other.x - position.x, other.z - position.z);
+                offset.length_squared() <= radius_squared
+            })
+    }
+}
+
+#[derive(Component)]
+pub struct CrowdAgent {
+    pub radius: f32,
+    pub neighbor_radius: f32,     // Дальность поиска соседей
+    pub separation_weight: f32,   // Сила расталкивания при перекрытии
+    pub avoidance_weight: f32,    // Сила уклонения от предсказанных столкновений
+    pub time_horizon: f32,        // На сколько секунд вперед предсказываем столкновения
+    pub surround_radius: f32,     // Ближе этого расстояния до цели враги расходятся по кругу
+    pub surround_distance: f32,   // Радиус кольца вокруг цели
+    /// Точка на кольце вокруг цели, назначенная этому агенту
+    pub surround_slot: Option<Vec3>,
+}
+
+impl Default for CrowdAgent {
+    fn default() -> Self {
+        Self {
+            radius: 0.6,
+            neighbor_radius: 4.0,
+            separation_weight: 4.0,
+            avoidance_weight: 2.0,
+            time_horizon: 1.0,
+            surround_radius: 6.0,
+            surround_distance: 2.0,
+            surround_slot: None,
+        }
+    }
+}
+
+// Радиус капсулы игрока для избегания
+const PLAYER_RADIUS: f32 = 0.3;
+
+fn rebuild_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    agents: Query<(Entity, &Transform), With<CrowdAgent>>,
+) {
+    spatial_hash.clear();
+    for (entity, transform) in agents.iter() {
+        spatial_hash.insert(entity, transform.translation);
+    }
+}
+
+/// Враги, которые видят игрока и подошли близко, равномерно распределяются
+/// по кольцу вокруг него, а не сходятся в одну точку
+fn assign_surround_slots(
+    player_query: Query<&Transform, With<Player>>,
+    mut agents: Query<(Entity, &Transform, &Perception, &mut CrowdAgent)>,
+) {
+    let player_position = player_query.get_single().ok().map(|transform| transform.translation);
+
+    let mut attackers: Vec<(Entity, f32)> = Vec::new();
+    for (entity, transform, perception, mut agent) in agents.iter_mut() {
+        agent.surround_slot = None;
+        let Some(target) = player_position else {
+            continue;
+        };
+        let offset = transform.translation - target;
+        let engaged = perception.sees_target
+            && Vec2::new(offset.x, offset.z).length() < agent.surround_radius;
+        if engaged {
+            attackers.push((entity, offset.z.atan2(offset.x)));
+        }
+    }
+
+    let Some(target) = player_position else {
+        return;
+    };
+    if attackers.is_empty() {
+        return;
+    }
+
+    // Сортируем по текущему углу, чтобы враги не пересекали друг другу дорогу к слотам
+    attackers.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let step = TAU / attackers.len() as f32;
+    // Поворачиваем кольцо так, чтобы слоты в среднем были ближе к текущим углам
+    let (sin_sum, cos_sum) = attackers.iter().enumerate().fold((0.0, 0.0), |(s, c), (i, (_, angle))| {
+        let delta = angle - i as f32 * step;
+        (s + delta.sin(), c + delta.cos())
+    });
+    let base_angle = sin_sum.atan2(cos_sum);
+
+    for (i, (entity, _)) in attackers.iter().enumerate() {
+        if let Ok((_, _, _, mut agent)) = agents.get_mut(*entity) {
+            let angle = base_angle + i as f32 * step;
+            let slot = target + Vec3::new(angle.cos(), 0.0, angle.sin()) * agent.surround_distance;
+            agent.surround_slot = Some(slot);
+        }
+    }
+}
+
+/// Boids-разделение плюс уклонение по времени до столкновения (упрощенный RVO):
+/// ИИ выставляет желаемую скорость, здесь она корректируется с учетом соседей
+fn apply_crowd_avoidance(
+    spatial_hash: Res<SpatialHash>,
+    player_query: Query<(&Transform, &player::Velocity), With<Player>>,
+    mut agents: Query<(Entity, &Transform, &Enemy, &CrowdAgent, Option<&Stats>, Has<Launched>, &mut Velocity)>,
+) {
+    let player = player_query.get_single().ok();
+
+    // Сначала считаем все новые скорости, потом записываем - чтобы результат
+    // не зависел от порядка обхода агентов
+    let mut updates: Vec<(Entity, Vec3)> = Vec::new();
+    for (entity, transform, enemy, agent, stats, launched, velocity) in agents.iter() {
+        // Подброшенного площадкой или взрывом не трогаем - он остается только препятствием для соседей
+        if launched {
+            continue;
+        }
+        let position = transform.translation;
+        // Скорость с учетом модификаторов: фазы босса, сложность, ускорение
+        let speed = stats.map_or(enemy.speed, |stats| stats.apply(Stat::MoveSpeed, enemy.speed));
+        let preferred = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+        let mut steering = Vec3::ZERO;
+
+        let mut obstacles: Vec<(Vec3, Vec3, f32)> = spatial_hash
+            .neighbors(position, agent.neighbor_radius)
+            .filter(|(other, _)| *other != entity)
+            .map(|(other, other_position)| {
+                let other_velocity = agents.get(other).map(|(.., v)| v.linvel).unwrap_or(Vec3::ZERO);
+                (other_position, other_velocity, agent.radius)
+            })
+            .collect();
+        if let Some((player_transform, player_velocity)) = player {
+            obstacles.push((player_transform.translation, player_velocity.0, PLAYER_RADIUS));
+        }
+
+        for (other_position, other_velocity, other_radius) in obstacles {
+            let offset = Vec3::new(position.x - other_position.x, 0.0, position.z - other_position.z);
+            let distance = offset.length();
+            let combined_radius = agent.radius + other_radius;
+
+            // Разделение: чем сильнее перекрытие, тем сильнее отталкивание
+            if distance < combined_radius * 1.5 {
+                let away = if distance > f32::EPSILON {
+                    offset / distance
+                } else {
+                    // Агенты в одной точке - расталкиваем в стороны по индексу сущности
+                    let angle = entity.index() as f32;
+                    Vec3::new(angle.cos(), 0.0, angle.sin())
+                };
+                let overlap = (combined_radius * 1.5 - distance) / (combined_radius * 1.5);
+                steering += away * overlap * agent.separation_weight * speed;
+            }
+
+            // Уклонение: ищем момент максимального сближения при текущих скоростях
+            let relative_velocity = preferred - Vec3::new(other_velocity.x, 0.0, other_velocity.z);
+            let speed_squared = relative_velocity.length_squared();
+            if speed_squared < f32::EPSILON {
+                continue;
+            }
+            let time_to_closest = -offset.dot(relative_velocity) / speed_squared;
+            if time_to_closest <= 0.0 || time_to_closest > agent.time_horizon {
+                continue;
+            }
+            let closest_offset = offset + relative_velocity * time_to_closest;
+            let closest_distance = closest_offset.length();
+            if closest_distance < combined_radius {
+                let away = closest_offset.normalize_or(offset.normalize_or_zero());
+                let urgency = 1.0 - time_to_closest / agent.time_horizon;
+                steering += away * urgency * agent.avoidance_weight * speed;
+            }
+        }
+
+        // Ограничиваем итоговую скорость, чтобы толкучка не разгоняла врагов
+        let max_speed = speed * 1.2;
+        let horizontal = (preferred + steering).clamp_length_max(max_speed);
+        updates.push((entity, Vec3::new(horizontal.x, velocity.linvel.y, horizontal.z)));
+    }
+
+    for (entity, new_velocity) in updates {
+        if let Ok((.., mut velocity)) = agents.get_mut(entity) {
+            velocity.linvel = new_velocity;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// src/data.rs - Общий загрузчик игровых данных из RON-файлов в assets/
+use std::fmt;
+use std::marker::PhantomData;
+
+use bevy::asset::{io::Reader, Asset, AssetLoader, LoadContext};
+use serde::de::DeserializeOwned;
+
+/// Загрузчик любого ассета, который описывается RON-файлом с заданным расширением
+/// (например `arena.waves.ron`)
+pub struct RonAssetLoader<A> {
+    extensions: &'static [&'static str],
+    _marker: PhantomData<fn() -> A>,
+}
+
+impl<A> RonAssetLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RonLoaderError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonLoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonLoaderError::Io(error) => write!(f, "не удалось прочитать файл: {error}"),
+            RonLoaderError::Ron(error) => write!(f, "ошибка в RON: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for RonLoaderError {}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<A, RonLoaderError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(RonLoaderError::Io)?;
+        ron::de::from_bytes(&bytes).map_err(RonLoaderError::Ron)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
@@ -0,0 +1,270 @@
+// src/death.rs - Смерть врагов: событие, растворение тела, разлетающиеся обломки и их уборка
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy::render::mesh::MeshAabb;
+use bevy_rapier3d::prelude::*;
+use crate::archetypes::ArchetypeName;
+use crate::enemies::{Enemy, Health};
+use crate::models::CharacterModelScene;
+use crate::state::{GameState, InGame};
+
+pub struct DeathPlugin;
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebrisBudget>()
+           .add_event::<EnemyKilled>()
+           .add_systems(Update, (
+               enemy_death_system,
+               update_corpses,
+               update_debris,
+               enforce_debris_budget,
+               log_kills,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Группа коллизий обломков - персонаж игрока их не замечает и не спотыкается
+pub const DEBRIS_GROUP: Group = Group::GROUP_5;
+
+// Длительность растворения тела (сек)
+const DISSOLVE_TIME: f32 = 0.6;
+// Сколько живут обломки и сколько из этого времени они уменьшаются перед исчезновением
+const DEBRIS_LIFETIME: f32 = 8.0;
+const DEBRIS_SHRINK_TIME: f32 = 1.0;
+
+#[derive(Event)]
+pub struct EnemyKilled {
+    pub enemy: Entity,
+    pub archetype: Option<String>,
+    pub killer: Option<Entity>,
+    pub position: Vec3,
+    pub impulse: Vec3,
+}
+
+/// Сколько обломков может лежать на арене одновременно.
+/// Лишние убираются, начиная с самых старых
+#[derive(Resource)]
+pub struct DebrisBudget {
+    pub max_pieces: usize,
+}
+
+impl Default for DebrisBudget {
+    fn default() -> Self {
+        Self { max_pieces: 120 }
+    }
+}
+
+/// Тело погибшего врага - проседает, сжимается и тает
+#[derive(Component)]
+struct Corpse {
+    timer: f32,
+    start_scale: Vec3,
+}
+
+#[derive(Component)]
+pub struct Debris {
+    age: f32,
+    scale: Vec3,
+}
+
+fn enemy_death_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    enemy_query: Query<(Entity, &Transform, &Health, &Mesh3d, &MeshMaterial3d<StandardMaterial>, Option<&ArchetypeName>, Option<&Children>), With<Enemy>>,
+    models: Query<&GlobalTransform, With<CharacterModelScene>>,
+    mut killed: EventWriter<EnemyKilled>,
+) {
+    for (entity, transform, health, mesh, material, archetype, children) in enemy_query.iter() {
+        if !health.is_dead() {
+            continue;
+        }
+        let (killer, impulse) = health.last_hit.map_or((None, Vec3::ZERO), |hit| (hit.source, hit.impulse));
+        killed.send(EnemyKilled {
+            enemy: entity,
+            archetype: archetype.map(|name| name.0.clone()),
+            killer,
+            position: transform.translation,
+            impulse,
+        });
+
+        // Сам враг исчезает сразу, чтобы ИИ, волны и счетчики его больше не видели.
+        // Вместо него остаются тело без физики и обломки
+        let Some(source) = materials.get(&material.0).cloned() else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let base_color = source.base_color;
+        let size = meshes.get(&mesh.0)
+            .and_then(|mesh| mesh.compute_aabb())
+            .map_or(Vec3::ONE, |aabb| Vec3::from(aabb.half_extents) * 2.0);
+
+        let model = children
+            .and_then(|children| children.iter().find_map(|child| models.get(*child).ok().map(|global| (*child, global))));
+        if let Some((model, global)) = model {
+            // Фигура врага скрыта под моделью - телом становится сама модель, отцепленная от врага
+            commands.entity(model).remove_parent_in_place().insert((
+                Corpse {
+                    timer: DISSOLVE_TIME,
+                    start_scale: global.compute_transform().scale,
+                },
+                StateScoped(InGame),
+            ));
+        } else {
+            commands.spawn((
+                Mesh3d(mesh.0.clone()),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    alpha_mode: AlphaMode::Blend,
+                    ..source
+                })),
+                *transform,
+                Corpse {
+                    timer: DISSOLVE_TIME,
+                    start_scale: transform.scale,
+                },
+                Name::new("Corpse"),
+                StateScoped(InGame),
+            ));
+        }
+
+        spawn_debris(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            transform.translation,
+            size,
+            base_color,
+            impulse,
+            entity.index(),
+        );
+
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+pub fn spawn_debris(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    position: Vec3,
+    size: Vec3,
+    color: Color,
+    impulse: Vec3,
+    seed: u32,
+) {
+    // Чем крупнее враг, тем больше кусков
+    let count = ((size.x * size.y * size.z) * 6.0).clamp(5.0, 20.0) as u32;
+    let piece = (size.min_element() * 0.3).clamp(0.15, 0.45);
+    let mesh = meshes.add(Cuboid::from_length(piece));
+    let material = materials.add(StandardMaterial {
+        base_color: color,
+        perceptual_roughness: 0.8,
+        ..default()
+    });
+
+    // Разброс без генератора случайных чисел: куски идут по спирали золотого угла,
+    // а номер сущности поворачивает всю спираль, чтобы смерти не выглядели одинаково
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let phase = (seed as f32 * 0.618_034).fract() * TAU;
+    for i in 0..count {
+        let t = (i as f32 + 0.5) / count as f32;
+        let angle = phase + i as f32 * GOLDEN_ANGLE;
+        let outward = Vec3::new(angle.cos(), 0.0, angle.sin());
+        let offset = outward * size.x * 0.35 + Vec3::Y * (t - 0.5) * size.y * 0.8;
+        let scale = Vec3::splat(0.7 + 0.6 * (i as f32 * 0.37).fract());
+
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(position + offset)
+                .with_rotation(Quat::from_euler(EulerRot::XYZ, angle, angle * 0.5, t * TAU))
+                .with_scale(scale),
+            RigidBody::Dynamic,
+            Collider::cuboid(piece * 0.5, piece * 0.5, piece * 0.5),
+            CollisionGroups::new(DEBRIS_GROUP, Group::ALL),
+            Velocity {
+                // Куски наследуют толчок убившего удара и разлетаются от центра
+                linvel: impulse + outward * (1.5 + 2.0 * t) + Vec3::Y * (2.0 + 3.0 * t),
+                angvel: Vec3::new(outward.z, 1.0, -outward.x) * 8.0,
+            },
+            Debris { age: 0.0, scale },
+            Name::new("Debris"),
+            StateScoped(InGame),
+        ));
+    }
+}
+
+fn update_corpses(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut corpses: Query<(Entity, &mut Corpse, &mut Transform, Option<&MeshMaterial3d<StandardMaterial>>)>,
+) {
+    for (entity, mut corpse, mut transform, material) in corpses.iter_mut() {
+        corpse.timer -= time.delta_secs();
+        if corpse.timer <= 0.0 {
+            // Копия материала удаляется вместе с последним хэндлом
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let progress = 1.0 - corpse.timer / DISSOLVE_TIME;
+        // Тело сплющивается к полу и расплывается в стороны
+        transform.scale = corpse.start_scale * Vec3::new(1.0 + progress * 0.3, 1.0 - progress * 0.8, 1.0 + progress * 0.3);
+        transform.translation.y -= time.delta_secs() * 0.8;
+        // Материалы модели общие для всех ее копий - модель только сплющивается и уходит в пол
+        if let Some(material) = material.and_then(|material| materials.get_mut(&material.0)) {
+            material.base_color.set_alpha(1.0 - progress);
+            material.emissive = LinearRgba::rgb(3.0, 0.8, 0.2) * (1.0 - progress);
+        }
+    }
+}
+
+fn update_debris(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut debris: Query<(Entity, &mut Debris, &mut Transform)>,
+) {
+    for (entity, mut piece, mut transform) in debris.iter_mut() {
+        piece.age += time.delta_secs();
+        let remaining = DEBRIS_LIFETIME - piece.age;
+        if remaining <= 0.0 {
+            commands.entity(entity).despawn();
+        } else if remaining < DEBRIS_SHRINK_TIME {
+            transform.scale = piece.scale * (remaining / DEBRIS_SHRINK_TIME);
+        }
+    }
+}
+
+fn enforce_debris_budget(
+    mut commands: Commands,
+    budget: Res<DebrisBudget>,
+    debris: Query<(Entity, &Debris)>,
+) {
+    // Истекшие куски уже удалены в update_debris
+    let mut pieces: Vec<(Entity, f32)> = debris
+        .iter()
+        .filter(|(_, piece)| piece.age < DEBRIS_LIFETIME)
+        .map(|(entity, piece)| (entity, piece.age))
+        .collect();
+    let count = pieces.len();
+    if count <= budget.max_pieces {
+        return;
+    }
+
+    // Самые старые - в начале
+    pieces.sort_by(|a, b| b.1.total_cmp(&a.1));
+    for (entity, _) in pieces.into_iter().take(count - budget.max_pieces) {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn log_kills(mut killed: EventReader<EnemyKilled>) {
+    for event in killed.read() {
+        debug!(
+            "Enemy {:?} ({:?}) killed by {:?} at {:?} (impulse {:?})",
+            event.enemy, event.archetype, event.killer, event.position, event.impulse
+        );
+    }
+}
@@ -1,18 +1,28 @@
 // src/enemies.rs - Полная система врагов
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::player::Player;
-use crate::weapons::Bullet;
+use crate::player::{self, Player};
+use crate::weapons::{Weapon, WeaponAim, fire_weapons};
+use crate::navigation::{NavAgent, update_nav_paths};
+use crate::perception::{Perception, PerceptionSet};
+use crate::crowd::{CrowdAgent, CrowdSet};
+use crate::archetypes::{AttackDefinition, AttackKind};
+use crate::melee::MeleeAttack;
+use crate::stats::{Stat, Stats};
+use crate::state::GameState;
+use crate::world::Launched;
 
 pub struct EnemiesPlugin;
 impl Plugin for EnemiesPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Update, (
-                enemy_ai_system,
-                bullet_enemy_collision,
-                enemy_death_system,
-            ))
-           .add_systems(Startup, spawn_initial_enemies);
+                enemy_ai_system
+                    .after(update_nav_paths)
+                    .after(PerceptionSet)
+                    .after(CrowdSet::Plan)
+                    .before(CrowdSet::Avoid),
+                ranged_enemy_aim.after(PerceptionSet).before(fire_weapons),
+            ).run_if(in_state(GameState::Playing)));
     }
 }
 
@@ -22,149 +32,192 @@ pub struct Enemy {
     pub target: Option<Entity>,
 }
 
+/// Атака врага из описания его типа
+#[derive(Component)]
+pub struct EnemyAttack(pub AttackDefinition);
+
+/// Кто нанес удар и куда толкнул цель
+#[derive(Clone, Copy)]
+pub struct Hit {
+    pub source: Option<Entity>,
+    pub impulse: Vec3, // Толчок в м/с - обломки погибшего разлетаются с этой скоростью
+}
+
+// Доля урона, которую забирает на себя броня
+const ARMOR_ABSORPTION: f32 = 0.66;
+
 #[derive(Component)]
 pub struct Health {
     pub current: f32,
+    pub max: f32,
+    pub armor: f32,
+    pub max_armor: f32,
+    /// Последнее попадание, для смерти - убивший удар
+    pub last_hit: Option<Hit>,
 }
 
 impl Health {
     pub fn new(max_health: f32) -> Self {
         Self {
             current: max_health,
+            max: max_health,
+            armor: 0.0,
+            max_armor: 200.0,
+            last_hit: None,
         }
     }
+
+    /// Доля оставшегося здоровья от 0 до 1
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 { self.current / self.max } else { 0.0 }
+    }
     
-    pub fn take_damage(&mut self, damage: f32) {
+    pub fn take_damage(&mut self, damage: f32, hit: Hit) {
+        if self.is_dead() {
+            return;
+        }
+        // Как в Quake: броня принимает часть урона, пока не кончится
+        let absorbed = (damage * ARMOR_ABSORPTION).min(self.armor);
+        self.armor -= absorbed;
+        self.current = (self.current - (damage - absorbed)).max(0.0);
+        self.last_hit = Some(hit);
+    }
+
+    /// Урон мимо брони - кислота разъедает ее насквозь
+    pub fn take_piercing_damage(&mut self, damage: f32, hit: Hit) {
+        if self.is_dead() {
+            return;
+        }
         self.current = (self.current - damage).max(0.0);
+        self.last_hit = Some(hit);
     }
-    
-    pub fn is_dead(&self) -> bool {
-        self.current <= 0.0
+
+    /// Лечение до максимума. Возвращает false, если здоровье уже полное
+    pub fn heal(&mut self, amount: f32) -> bool {
+        if self.current >= self.max {
+            return false;
+        }
+        self.current = (self.current + amount).min(self.max);
+        true
     }
-}
 
-fn spawn_initial_enemies(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    // Спавним несколько врагов вокруг игрока
-    let spawn_positions = vec![
-        Vec3::new(5.0, 1.0, 5.0),
-        Vec3::new(-5.0, 1.0, 5.0),
-        Vec3::new(5.0, 1.0, -5.0),
-    ];
+    pub fn add_armor(&mut self, amount: f32) -> bool {
+        if self.armor >= self.max_armor {
+            return false;
+        }
+        self.armor = (self.armor + amount).min(self.max_armor);
+        true
+    }
     
-    for pos in spawn_positions {
-        spawn_enemy(&mut commands, &mut meshes, &mut materials, pos);
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
     }
 }
 
-pub fn spawn_enemy(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    position: Vec3,
-) {
-    commands.spawn((
-        // Визуал - красный куб
-        Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.8, 0.2, 0.2), // Красный цвет
-            ..default()
-        })),
-        Transform::from_translation(position),
-        
-        // Физика
-        RigidBody::Dynamic,
-        Velocity::default(),
-        Collider::cuboid(0.5, 0.5, 0.5),
-        LockedAxes::ROTATION_LOCKED, // Не позволяем врагу вращаться
-        
-        // Компоненты врага
-        Enemy {
-            speed: 3.0,
-            target: None,
-        },
-        Health::new(50.0),
-        
-        Name::new("Enemy"),
-    ));
-}
-
+// Подброшенные площадками и ускорителями враги не управляют собой, пока летят
 fn enemy_ai_system(
     player_query: Query<Entity, With<Player>>,
-    player_transform_query: Query<&Transform, With<Player>>,
-    mut enemy_query: Query<(&Transform, &mut Enemy, &mut Velocity), (Without<Player>, With<Enemy>)>,
+    mut enemy_query: Query<(&Transform, &mut Enemy, &EnemyAttack, Option<&MeleeAttack>, &mut NavAgent, &Perception, &CrowdAgent, &mut Velocity, Option<&Stats>), (Without<Player>, With<Enemy>, Without<Launched>)>,
 ) {
-    let Ok(player_entity) = player_query.get_single() else {
-        return;
-    };
-    
-    let Ok(player_transform) = player_transform_query.get_single() else {
-        return;
-    };
+    let player_entity = player_query.get_single().ok();
     
-    for (enemy_transform, mut enemy, mut velocity) in enemy_query.iter_mut() {
-        // Устанавливаем цель (игрока)
-        enemy.target = Some(player_entity);
+    for (enemy_transform, mut enemy, attack, melee, mut agent, perception, crowd, mut velocity, stats) in enemy_query.iter_mut() {
+        // Враг знает о игроке только то, что увидел или услышал
+        let Some(last_known_position) = perception.last_known_position else {
+            enemy.target = None;
+            agent.goal = None;
+            velocity.linvel = Vec3::ZERO;
+            continue;
+        };
+        enemy.target = player_entity;
+        // Рядом с игроком занимаем свой слот на кольце окружения
+        let goal = crowd.surround_slot.unwrap_or(last_known_position);
+        agent.goal = Some(goal);
         
-        // Вычисляем направление к игроку
-        let direction = (player_transform.translation - enemy_transform.translation).normalize_or_zero();
+        // Идем по пути из навигационной сетки, чтобы обходить препятствия
+        let direction = agent.steering_direction(enemy_transform.translation);
+        
+        // Дошли до цели (слота или последней известной позиции) - стоим
+        let reached = Vec2::new(
+            goal.x - enemy_transform.translation.x,
+            goal.z - enemy_transform.translation.z,
+        ).length() < agent.waypoint_radius;
+        // Стрелок, который видит игрока на дистанции выстрела, не подходит ближе
+        let in_firing_range = attack.0.kind == AttackKind::Ranged
+            && perception.sees_target
+            && enemy_transform.translation.distance(last_known_position) <= attack.0.range;
+        // Во время удара стоим, чтобы замах можно было прочитать
+        let attacking = melee.is_some_and(MeleeAttack::is_attacking);
+        let speed = if reached || in_firing_range || attacking {
+            0.0
+        } else {
+            stats.map_or(enemy.speed, |stats| stats.apply(Stat::MoveSpeed, enemy.speed))
+        };
 
         velocity.linvel = Vec3::new(
-            direction.x * enemy.speed,
+            direction.x * speed,
             0.0,
-            direction.z * enemy.speed,
+            direction.z * speed,
         );
-        // Двигаемся к игроку (только по X и Z, игнорируем Y)
-        //let movement = Vec3::new(
-        //    direction.x * enemy.speed * time.delta_secs(),
-        //    0.0,
-        //    direction.z * enemy.speed * time.delta_secs(),
-        //);
-                //enemy_transform.translation += movement;
     }
 }
 
-fn bullet_enemy_collision(
-    mut commands: Commands,
-    mut collision_events: EventReader<CollisionEvent>,
-    bullet_query: Query<&Bullet>,
-    mut enemy_query: Query<&mut Health, With<Enemy>>,
+/// Враги-стрелки целятся с упреждением: пуля летит туда, где игрок окажется
+fn ranged_enemy_aim(
+    player_query: Query<(&Transform, &player::Velocity), With<Player>>,
+    mut shooters: Query<(&Transform, &EnemyAttack, &Perception, &Weapon, &mut WeaponAim), With<Enemy>>,
 ) {
-    for collision_event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
-            // Проверяем, столкнулись ли пуля и враг
-            let (bullet_entity, enemy_entity) = if bullet_query.contains(*entity1) {
-                (*entity1, *entity2)
-            } else if bullet_query.contains(*entity2) {
-                (*entity2, *entity1)
-            } else {
-                continue;
-            };
-            
-            // Получаем урон от пули
-            if let Ok(bullet) = bullet_query.get(bullet_entity) {
-                // Наносим урон врагу
-                if let Ok(mut enemy_health) = enemy_query.get_mut(enemy_entity) {
-                    enemy_health.take_damage(bullet.damage);
-                    
-                    // Удаляем пулю
-                    commands.entity(bullet_entity).despawn();
-                }
-            }
+    let player = player_query.get_single().ok();
+
+    for (transform, attack, perception, weapon, mut aim) in shooters.iter_mut() {
+        aim.trigger = false;
+        let Some((player_transform, player_velocity)) = player else {
+            continue;
+        };
+        if !perception.sees_target {
+            continue;
+        }
+
+        let target = player_transform.translation;
+        let muzzle = Weapon::muzzle_position(transform, target - transform.translation);
+        if muzzle.distance(target) > attack.0.range {
+            continue;
         }
+
+        aim.direction = lead_direction(muzzle, target, player_velocity.0, weapon.projectile_speed);
+        aim.trigger = true;
     }
 }
 
-fn enemy_death_system(
-    mut commands: Commands,
-    enemy_query: Query<(Entity, &Health), With<Enemy>>,
-) {
-    for (enemy_entity, health) in enemy_query.iter() {
-        if health.is_dead() {
-            commands.entity(enemy_entity).despawn();
-        }
+/// Направление выстрела, при котором пуля со скоростью `speed` встретит цель,
+/// движущуюся с постоянной скоростью. Если перехват невозможен - стреляем прямо в цель
+fn lead_direction(muzzle: Vec3, target: Vec3, target_velocity: Vec3, speed: f32) -> Vec3 {
+    let to_target = target - muzzle;
+    // |to_target + v*t| = speed*t  ->  a*t^2 + b*t + c = 0
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * to_target.dot(target_velocity);
+    let c = to_target.length_squared();
+
+    let time = if a.abs() < f32::EPSILON {
+        (b.abs() > f32::EPSILON).then(|| -c / b)
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        (discriminant >= 0.0).then(|| {
+            let root = discriminant.sqrt();
+            let t1 = (-b - root) / (2.0 * a);
+            let t2 = (-b + root) / (2.0 * a);
+            // Берем ближайшее положительное время встречи
+            match (t1 > 0.0, t2 > 0.0) {
+                (true, true) => t1.min(t2),
+                (true, false) => t1,
+                (false, true) => t2,
+                (false, false) => -1.0,
+            }
+        })
+    };
+
+    match time {
+        Some(t) if t > 0.0 => (to_target + target_velocity * t).normalize_or_zero(),
+        _ => to_target.normalize_or_zero(),
     }
 }
@@ -0,0 +1,229 @@
+// src/hazards.rs - Опасные зоны уровня: лава, кислота, шипы наносят урон всему живому внутри,
+// зоны замедления и сноса меняют движение. Любую зону можно включать и выключать по таймеру
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::crowd::CrowdSet;
+use crate::enemies::{Enemy, Health, Hit};
+use crate::player::{move_kinematic_player_by_velocity, Player};
+use crate::state::GameState;
+use crate::stats::{ModifierOp, ModifierSource, Stacking, Stat, StatModifier, Stats};
+use crate::world::bodies_inside;
+
+pub struct HazardsPlugin;
+impl Plugin for HazardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+               cycle_hazards,
+               damage_in_hazards,
+               slow_in_hazards,
+               // Скорость врагу заново задает ИИ каждый кадр - снос добавляем последним
+               push_enemies.after(CrowdSet::Avoid),
+           ).chain().run_if(in_state(GameState::Playing)))
+           .add_systems(FixedUpdate, push_player
+               .after(move_kinematic_player_by_velocity)
+               .run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Замедление держится столько после выхода из зоны (сек) - и продлевается, пока в ней стоят
+const SLOW_LINGER: f32 = 0.25;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DamageKind {
+    /// Лава и огонь: броня принимает часть урона как обычно
+    Fire,
+    /// Кислота: урон идет мимо брони
+    Acid,
+    Spikes,
+}
+
+impl DamageKind {
+    pub fn color(self) -> (Color, LinearRgba) {
+        match self {
+            DamageKind::Fire => (Color::srgb(1.0, 0.35, 0.05), LinearRgba::rgb(4.0, 1.0, 0.1)),
+            DamageKind::Acid => (Color::srgb(0.3, 0.9, 0.1), LinearRgba::rgb(0.6, 2.0, 0.2)),
+            DamageKind::Spikes => (Color::srgb(0.6, 0.6, 0.65), LinearRgba::BLACK),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum HazardEffect {
+    /// `damage` раз в `interval` секунд каждому, у кого есть здоровье
+    Damage {
+        kind: DamageKind,
+        damage: f32,
+        #[serde(default = "default_interval")]
+        interval: f32,
+    },
+    /// Множитель скорости движения (0.5 - вдвое медленнее)
+    Slow { factor: f32 },
+    /// Снос со скоростью `speed` (м/с) в сторону `direction` - градусы вокруг вертикали, 0 - на -Z
+    Push { direction: f32, speed: f32 },
+}
+
+fn default_interval() -> f32 {
+    0.5
+}
+
+impl HazardEffect {
+    pub fn color(&self) -> (Color, LinearRgba) {
+        match *self {
+            HazardEffect::Damage { kind, .. } => kind.color(),
+            HazardEffect::Slow { .. } => (Color::srgb(0.35, 0.25, 0.15), LinearRgba::BLACK),
+            HazardEffect::Push { .. } => (Color::srgb(0.2, 0.6, 0.9), LinearRgba::rgb(0.1, 0.4, 0.8)),
+        }
+    }
+}
+
+/// Расписание зоны: `on` секунд работает, `off` секунд отдыхает, начиная со сдвига `offset`
+#[derive(Deserialize, Component, Clone, Copy)]
+pub struct HazardCycle {
+    pub on: f32,
+    pub off: f32,
+    #[serde(default)]
+    pub offset: f32,
+}
+
+impl HazardCycle {
+    fn is_on(&self, time: f32) -> bool {
+        let period = self.on + self.off;
+        period <= 0.0 || (time + self.offset).rem_euclid(period) < self.on
+    }
+}
+
+#[derive(Component)]
+pub struct HazardZone {
+    pub effect: HazardEffect,
+    pub active: bool,
+    /// До следующего удара (сек), для зон урона
+    tick: f32,
+    /// Сколько зона существует - по этому времени идет расписание
+    age: f32,
+}
+
+impl HazardZone {
+    pub fn new(effect: HazardEffect) -> Self {
+        Self { effect, active: true, tick: 0.0, age: 0.0 }
+    }
+}
+
+/// Выключенная зона прячется; расписание отсчитывается с появления уровня
+fn cycle_hazards(
+    time: Res<Time>,
+    mut zones: Query<(&mut HazardZone, &HazardCycle, &mut Visibility)>,
+) {
+    for (mut zone, cycle, mut visibility) in zones.iter_mut() {
+        zone.age += time.delta_secs();
+        let active = cycle.is_on(zone.age);
+        if zone.active != active {
+            zone.active = active;
+            // Включенная зона бьет сразу, а не через интервал
+            zone.tick = 0.0;
+            *visibility = if active { Visibility::Inherited } else { Visibility::Hidden };
+        }
+    }
+}
+
+fn damage_in_hazards(
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    mut zones: Query<(Entity, &mut HazardZone)>,
+    mut victims: Query<&mut Health>,
+) {
+    let context = rapier_context.single();
+    for (entity, mut zone) in zones.iter_mut() {
+        let HazardEffect::Damage { kind, damage, interval } = zone.effect else {
+            continue;
+        };
+        if !zone.active {
+            continue;
+        }
+        zone.tick -= time.delta_secs();
+        if zone.tick > 0.0 {
+            continue;
+        }
+        zone.tick = interval;
+        for other in bodies_inside(&context, entity) {
+            let Ok(mut health) = victims.get_mut(other) else {
+                continue;
+            };
+            let hit = Hit { source: None, impulse: Vec3::ZERO };
+            match kind {
+                DamageKind::Acid => health.take_piercing_damage(damage, hit),
+                DamageKind::Fire | DamageKind::Spikes => health.take_damage(damage, hit),
+            }
+        }
+    }
+}
+
+fn slow_in_hazards(
+    rapier_context: ReadRapierContext,
+    zones: Query<(Entity, &HazardZone)>,
+    mut stats: Query<&mut Stats>,
+) {
+    let context = rapier_context.single();
+    for (entity, zone) in zones.iter() {
+        let HazardEffect::Slow { factor } = zone.effect else {
+            continue;
+        };
+        if !zone.active {
+            continue;
+        }
+        for other in bodies_inside(&context, entity) {
+            if let Ok(mut stats) = stats.get_mut(other) {
+                let modifier = StatModifier::new(Stat::MoveSpeed, ModifierOp::Multiply(factor), ModifierSource::Hazard);
+                stats.add(modifier.with_duration(SLOW_LINGER), Stacking::Replace);
+            }
+        }
+    }
+}
+
+fn push_velocity(effect: &HazardEffect) -> Option<Vec3> {
+    match *effect {
+        HazardEffect::Push { direction, speed } => {
+            Some(Quat::from_rotation_y(direction.to_radians()) * Vec3::NEG_Z * speed)
+        }
+        _ => None,
+    }
+}
+
+fn push_enemies(
+    rapier_context: ReadRapierContext,
+    zones: Query<(Entity, &HazardZone)>,
+    mut enemies: Query<&mut Velocity, With<Enemy>>,
+) {
+    let context = rapier_context.single();
+    for (entity, zone) in zones.iter().filter(|(_, zone)| zone.active) {
+        let Some(push) = push_velocity(&zone.effect) else {
+            continue;
+        };
+        for other in bodies_inside(&context, entity) {
+            if let Ok(mut velocity) = enemies.get_mut(other) {
+                velocity.linvel += push;
+            }
+        }
+    }
+}
+
+/// Игрока сносит поверх его собственного движения за шаг
+fn push_player(
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    zones: Query<(Entity, &HazardZone)>,
+    mut player_query: Query<(Entity, &mut KinematicCharacterController), With<Player>>,
+) {
+    let Ok((player, mut controller)) = player_query.get_single_mut() else {
+        return;
+    };
+    let context = rapier_context.single();
+    let push: Vec3 = zones
+        .iter()
+        .filter(|(entity, zone)| zone.active && context.intersection_pair(*entity, player) == Some(true))
+        .filter_map(|(_, zone)| push_velocity(&zone.effect))
+        .sum();
+    if push != Vec3::ZERO {
+        controller.translation = Some(controller.translation.unwrap_or(Vec3::ZERO) + push * time.delta_secs());
+    }
+}
@@ -0,0 +1,471 @@
+// src/highscores.rs - Таблица рекордов на диске: отдельная для каждого уровня и сложности.
+// Файл версионирован и защищен контрольной суммой: поврежденный или отредактированный
+// вручную файл откладывается в сторону (*.bad), и игра начинает с пустой таблицы
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::score::{show_score_breakdown, Score};
+use crate::state::{GameOverInput, GameOverScreen, GameState};
+use crate::stats::Difficulty;
+use crate::level::CurrentLevel;
+
+pub struct HighScoresPlugin;
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_high_scores)
+           // Пока вводится имя, Enter и Escape не должны перезапускать уровень
+           .configure_sets(Update, GameOverInput.run_if(not(resource_exists::<NameEntry>)))
+           .add_systems(OnEnter(GameState::GameOver), begin_high_score.after(show_score_breakdown))
+           .add_systems(OnExit(GameState::GameOver), cancel_name_entry)
+           .add_systems(Update, (
+               enter_name,
+               update_high_score_panel,
+           ).chain().after(GameOverInput).run_if(in_state(GameState::GameOver)));
+    }
+}
+
+const HIGH_SCORES_FILE: &str = "highscores.ron";
+const FILE_VERSION: u32 = 1;
+const TABLE_SIZE: usize = 10;
+const MAX_NAME_LENGTH: usize = 12;
+// Соль контрольной суммы - без нее сумму легко пересчитать после правки файла
+const CHECKSUM_SALT: u64 = 0x5eed_a12e_4a5c_0de5;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub kills: u32,
+    pub time: f32,        // Длительность забега (сек)
+    pub accuracy: f32,    // Доля попаданий, 0..1
+    pub seed: Option<u64>,
+    pub version: String,  // Версия игры, в которой поставлен рекорд
+}
+
+/// Формат файла на диске
+#[derive(Serialize, Deserialize)]
+struct HighScoreFile {
+    version: u32,
+    tables: BTreeMap<String, Vec<HighScoreEntry>>,
+    checksum: u64,
+}
+
+/// Таблицы по ключу "уровень/сложность", в каждой не больше `TABLE_SIZE` записей по убыванию очков
+#[derive(Resource)]
+pub struct HighScores {
+    path: PathBuf,
+    tables: BTreeMap<String, Vec<HighScoreEntry>>,
+    /// Место последней записи - подсвечивается в таблице
+    last_entry: Option<(String, usize)>,
+}
+
+impl HighScores {
+    fn load(path: PathBuf) -> Self {
+        let tables = match read_file(&path) {
+            Ok(tables) => tables,
+            Err(error) => {
+                warn!("High scores file {:?} is damaged ({}), starting with an empty table", path, error);
+                let backup = path.with_extension("ron.bad");
+                if let Err(error) = fs::rename(&path, &backup) {
+                    warn!("Could not move damaged high scores to {:?}: {}", backup, error);
+                }
+                BTreeMap::new()
+            }
+        };
+        Self { path, tables, last_entry: None }
+    }
+
+    fn save(&self) {
+        let file = HighScoreFile {
+            version: FILE_VERSION,
+            checksum: checksum(&self.tables),
+            tables: self.tables.clone(),
+        };
+        let text = match ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default()) {
+            Ok(text) => text,
+            Err(error) => {
+                error!("Could not serialize high scores: {}", error);
+                return;
+            }
+        };
+        // Через временный файл, чтобы сбой посреди записи не испортил старую таблицу
+        let temporary = self.path.with_extension("ron.tmp");
+        if let Err(error) = fs::write(&temporary, text).and_then(|_| fs::rename(&temporary, &self.path)) {
+            error!("Could not save high scores to {:?}: {}", self.path, error);
+        }
+    }
+
+    pub fn table(&self, key: &str) -> &[HighScoreEntry] {
+        self.tables.get(key).map_or(&[], Vec::as_slice)
+    }
+
+    fn qualifies(&self, key: &str, score: u32) -> bool {
+        let table = self.table(key);
+        score > 0 && (table.len() < TABLE_SIZE || table.last().is_some_and(|last| score > last.score))
+    }
+
+    fn insert(&mut self, key: &str, entry: HighScoreEntry) {
+        let table = self.tables.entry(key.to_string()).or_default();
+        // При равенстве очков старая запись остается выше
+        let rank = table.iter().position(|other| entry.score > other.score).unwrap_or(table.len());
+        table.insert(rank, entry);
+        table.truncate(TABLE_SIZE);
+        self.last_entry = (rank < TABLE_SIZE).then(|| (key.to_string(), rank));
+        self.save();
+    }
+}
+
+pub fn table_key(level: &str, difficulty: Difficulty) -> String {
+    format!("{}/{:?}", level, difficulty)
+}
+
+fn read_file(path: &Path) -> Result<BTreeMap<String, Vec<HighScoreEntry>>, String> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        // Первый запуск - рекордов еще нет
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(error) => return Err(error.to_string()),
+    };
+    let file: HighScoreFile = ron::from_str(&text).map_err(|error| error.to_string())?;
+    if file.version != FILE_VERSION {
+        return Err(format!("unsupported version {}", file.version));
+    }
+    if checksum(&file.tables) != file.checksum {
+        return Err("checksum mismatch".to_string());
+    }
+    for (key, table) in &file.tables {
+        let sorted = table.windows(2).all(|pair| pair[0].score >= pair[1].score);
+        let valid = table.iter().all(|entry| {
+            entry.name.chars().count() <= MAX_NAME_LENGTH
+                && entry.time.is_finite()
+                && (0.0..=1.0).contains(&entry.accuracy)
+        });
+        if table.len() > TABLE_SIZE || !sorted || !valid {
+            return Err(format!("invalid table '{}'", key));
+        }
+    }
+    Ok(file.tables)
+}
+
+/// FNV-1a по сериализованным таблицам
+fn checksum(tables: &BTreeMap<String, Vec<HighScoreEntry>>) -> u64 {
+    let text = ron::to_string(tables).unwrap_or_default();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325 ^ CHECKSUM_SALT;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Рекорд, для которого игрок сейчас вводит имя
+#[derive(Resource)]
+struct NameEntry {
+    key: String,
+    entry: HighScoreEntry,
+}
+
+#[derive(Component)]
+struct HighScorePanel;
+
+fn load_high_scores(mut commands: Commands) {
+    commands.insert_resource(HighScores::load(PathBuf::from(HIGH_SCORES_FILE)));
+}
+
+fn begin_high_score(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    level: Res<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+    mut high_scores: ResMut<HighScores>,
+    screens: Query<Entity, With<GameOverScreen>>,
+) {
+    high_scores.last_entry = None;
+    let key = table_key(&level.name, *difficulty);
+    if high_scores.qualifies(&key, score.total) {
+        commands.insert_resource(NameEntry {
+            key,
+            entry: HighScoreEntry {
+                name: String::new(),
+                score: score.total,
+                kills: score.kills,
+                time: score.time,
+                accuracy: score.accuracy(),
+                seed: level.seed,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+        });
+    }
+
+    let Ok(screen) = screens.get_single() else {
+        return;
+    };
+    let panel = commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.3)),
+        TextLayout::new_with_justify(JustifyText::Center),
+        HighScorePanel,
+    )).id();
+    // После заголовка и разбивки очков, перед подсказкой
+    commands.entity(screen).insert_children(2, &[panel]);
+}
+
+fn cancel_name_entry(mut commands: Commands) {
+    commands.remove_resource::<NameEntry>();
+}
+
+fn enter_name(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    gamepads: Query<&Gamepad>,
+    entry: Option<ResMut<NameEntry>>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    let Some(mut entry) = entry else {
+        keys.clear();
+        return;
+    };
+
+    // Геймпадом имя не набрать - A сохраняет рекорд под именем по умолчанию
+    let mut confirmed = gamepads.iter().any(|gamepad| gamepad.just_pressed(GamepadButton::South));
+    let mut skipped = false;
+    let name = &mut entry.entry.name;
+    for event in keys.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => confirmed = true,
+            Key::Escape => skipped = true,
+            Key::Backspace => {
+                name.pop();
+            }
+            Key::Space => name.push(' '),
+            Key::Character(text) => name.extend(
+                text.chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                    .flat_map(char::to_uppercase),
+            ),
+            _ => {}
+        }
+        while name.chars().count() > MAX_NAME_LENGTH {
+            name.pop();
+        }
+    }
+
+    if skipped {
+        commands.remove_resource::<NameEntry>();
+    } else if confirmed {
+        let mut record = entry.entry.clone();
+        record.name = match record.name.trim() {
+            "" => "PLAYER".to_string(),
+            name => name.to_string(),
+        };
+        info!("New high score: {} - {}", record.name, record.score);
+        high_scores.insert(&entry.key, record);
+        commands.remove_resource::<NameEntry>();
+    }
+}
+
+fn update_high_score_panel(
+    entry: Option<Res<NameEntry>>,
+    high_scores: Res<HighScores>,
+    level: Res<CurrentLevel>,
+    difficulty: Res<Difficulty>,
+    mut panel_query: Query<&mut Text, With<HighScorePanel>>,
+) {
+    let Ok(mut text) = panel_query.get_single_mut() else {
+        return;
+    };
+    if let Some(entry) = entry {
+        text.0 = format!(
+            "New high score!\nEnter your name: {}_\nEnter - save    Esc - skip",
+            entry.entry.name
+        );
+        return;
+    }
+
+    let key = table_key(&level.name, *difficulty);
+    let table = high_scores.table(&key);
+    let mut lines = vec![format!("High scores - {} ({:?})", level.name, *difficulty)];
+    if table.is_empty() {
+        lines.push("No records yet".to_string());
+    }
+    for (rank, record) in table.iter().enumerate() {
+        let marker = if high_scores.last_entry.as_ref() == Some(&(key.clone(), rank)) { ">" } else { " " };
+        let seed = record.seed.map_or(String::new(), |seed| format!("  seed {}", seed));
+        lines.push(format!(
+            "{} {}. {}  {}  ({} kills, {}:{:02}, {:.0}%{})  v{}",
+            marker,
+            rank + 1,
+            record.name,
+            record.score,
+            record.kills,
+            record.time as u32 / 60,
+            record.time as u32 % 60,
+            record.accuracy * 100.0,
+            seed,
+            record.version,
+        ));
+    }
+    text.0 = lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, score: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            name: name.to_string(),
+            score,
+            kills: 0,
+            time: 60.0,
+            accuracy: 0.5,
+            seed: None,
+            version: "test".to_string(),
+        }
+    }
+
+    /// Отдельный файл на каждый тест - тесты идут параллельно
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("highscores-{}-{}.ron", name, std::process::id()))
+    }
+
+    fn write(path: &Path, file: &HighScoreFile) {
+        fs::write(path, ron::to_string(file).unwrap()).unwrap();
+    }
+
+    fn tables(scores: &[u32]) -> BTreeMap<String, Vec<HighScoreEntry>> {
+        let table = scores.iter().map(|&score| entry("AAA", score)).collect();
+        BTreeMap::from([("arena/Normal".to_string(), table)])
+    }
+
+    fn scores(high_scores: &HighScores, key: &str) -> Vec<u32> {
+        high_scores.table(key).iter().map(|entry| entry.score).collect()
+    }
+
+    #[test]
+    fn read_file_accepts_valid_file() {
+        let path = temp_path("valid");
+        let tables = tables(&[300, 200, 100]);
+        write(&path, &HighScoreFile { version: FILE_VERSION, checksum: checksum(&tables), tables });
+        let read = read_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read["arena/Normal"].len(), 3);
+    }
+
+    #[test]
+    fn read_file_missing_is_empty() {
+        assert!(read_file(&temp_path("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_file_rejects_checksum_mismatch() {
+        let path = temp_path("checksum");
+        let tables = tables(&[300, 200, 100]);
+        let checksum = checksum(&tables) ^ 1;
+        write(&path, &HighScoreFile { version: FILE_VERSION, checksum, tables });
+        let result = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(result.unwrap_err(), "checksum mismatch");
+    }
+
+    #[test]
+    fn read_file_rejects_wrong_version() {
+        let path = temp_path("version");
+        let tables = tables(&[300]);
+        write(&path, &HighScoreFile { version: FILE_VERSION + 1, checksum: checksum(&tables), tables });
+        let result = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().starts_with("unsupported version"));
+    }
+
+    #[test]
+    fn read_file_rejects_unsorted_table() {
+        let path = temp_path("unsorted");
+        let tables = tables(&[100, 300, 200]);
+        write(&path, &HighScoreFile { version: FILE_VERSION, checksum: checksum(&tables), tables });
+        let result = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().starts_with("invalid table"));
+    }
+
+    #[test]
+    fn read_file_rejects_oversized_table() {
+        let path = temp_path("oversized");
+        let scores: Vec<u32> = (0..=TABLE_SIZE as u32).rev().map(|score| score + 1).collect();
+        let tables = tables(&scores);
+        write(&path, &HighScoreFile { version: FILE_VERSION, checksum: checksum(&tables), tables });
+        let result = read_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(result.unwrap_err().starts_with("invalid table"));
+    }
+
+    #[test]
+    fn qualifies_until_table_is_full() {
+        let path = temp_path("qualifies");
+        let mut high_scores = HighScores { path: path.clone(), tables: BTreeMap::new(), last_entry: None };
+        let key = "arena/Normal";
+        assert!(!high_scores.qualifies(key, 0));
+        assert!(high_scores.qualifies(key, 1));
+
+        for score in (1..=TABLE_SIZE as u32).map(|score| score * 10) {
+            high_scores.insert(key, entry("AAA", score));
+        }
+        fs::remove_file(&path).unwrap();
+        // Таблица полна: нужно строго больше последнего места
+        assert!(!high_scores.qualifies(key, 5));
+        assert!(!high_scores.qualifies(key, 10));
+        assert!(high_scores.qualifies(key, 11));
+    }
+
+    #[test]
+    fn insert_keeps_older_entry_above_on_tie() {
+        let path = temp_path("tie");
+        let mut high_scores = HighScores { path: path.clone(), tables: BTreeMap::new(), last_entry: None };
+        let key = "arena/Normal";
+        high_scores.insert(key, entry("OLD", 100));
+        high_scores.insert(key, entry("NEW", 100));
+        high_scores.insert(key, entry("TOP", 200));
+        fs::remove_file(&path).unwrap();
+
+        let names: Vec<&str> = high_scores.table(key).iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["TOP", "OLD", "NEW"]);
+        assert_eq!(high_scores.last_entry, Some((key.to_string(), 0)));
+    }
+
+    #[test]
+    fn insert_truncates_at_table_size() {
+        let path = temp_path("truncate");
+        let mut high_scores = HighScores { path: path.clone(), tables: BTreeMap::new(), last_entry: None };
+        let key = "arena/Normal";
+        for score in 1..=TABLE_SIZE as u32 + 3 {
+            high_scores.insert(key, entry("AAA", score * 10));
+        }
+        let expected: Vec<u32> = (4..=TABLE_SIZE as u32 + 3).rev().map(|score| score * 10).collect();
+        assert_eq!(scores(&high_scores, key), expected);
+
+        // Запись ниже последнего места не попадает в таблицу
+        high_scores.insert(key, entry("LOW", 1));
+        assert_eq!(scores(&high_scores, key).len(), TABLE_SIZE);
+        assert_eq!(high_scores.last_entry, None);
+
+        // Сохраненный файл читается обратно
+        let read = read_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(read[key].len(), TABLE_SIZE);
+    }
+}
@@ -0,0 +1,787 @@
+// src/level.rs - Уровни из файлов assets/levels/*.level.ron: геометрия из простых фигур,
+// материалы, свет, точки спавна врагов и предметов, триггеры.
+// Консольная команда `map <name>` загружает другой уровень
+use std::collections::HashMap;
+use std::path::Path;
+
+use bevy::pbr::CascadeShadowConfig;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::boss::BossArenaTrigger;
+use crate::console::{Console, ConsoleAppExt, ConsoleCommand};
+use crate::data::RonAssetLoader;
+use crate::enemies::Health;
+use crate::hazards::{HazardCycle, HazardEffect, HazardZone};
+use crate::models::{LevelModel, ModelCollider};
+use crate::movers::{BlockedBehavior, Easing, Mover, MoverButton, MoverMode, MoverTrigger, MoverWaypoint};
+use crate::pickups::PickupSpawner;
+use crate::props::{Destructible, Explosion};
+use crate::scripts::{ScriptAction, ScriptCondition, ScriptTrigger};
+use crate::state::{GameState, InGame, RestartLevel};
+use crate::weapons::Faction;
+use crate::world::{Affects, BoostStrip, EnemySpawnPoint, Ground, JumpPad, Teleporter};
+
+pub struct LevelPlugin;
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LevelDefinition>()
+           .register_asset_loader(RonAssetLoader::<LevelDefinition>::new(&["level.ron"]))
+           .init_resource::<CurrentLevel>()
+           .register_console_command("map", "map <name> - load assets/levels/<name>.level.ron")
+           .add_systems(Startup, load_initial_level)
+           .add_systems(OnEnter(InGame), spawn_level)
+           .add_systems(Update, (
+               recover_failed_level.run_if(in_state(GameState::Boot)),
+               map_command,
+           ));
+    }
+}
+
+const DEFAULT_LEVEL: &str = "arena";
+// Насколько уменьшается форма подвижного тела при проверке, не зажат ли кто-то:
+// стоящие вплотную и сверху не должны считаться препятствием
+const MOVER_PROBE_MARGIN: f32 = 0.1;
+
+/// Описание уровня
+#[derive(Asset, TypePath, Deserialize)]
+pub struct LevelDefinition {
+    /// Где появляется игрок
+    #[serde(default = "default_player_start")]
+    pub player_start: [f32; 3],
+    /// Волны врагов для этого уровня
+    pub waves: String,
+    #[serde(default)]
+    pub materials: HashMap<String, LevelMaterial>,
+    pub brushes: Vec<Brush>,
+    /// glTF-сцены с геометрией и маркерами
+    #[serde(default)]
+    pub models: Vec<LevelModelDefinition>,
+    #[serde(default)]
+    pub lights: Vec<LevelLight>,
+    #[serde(default)]
+    pub spawn_points: Vec<LevelSpawnPoint>,
+    #[serde(default)]
+    pub pickups: Vec<LevelPickup>,
+    #[serde(default)]
+    pub jump_pads: Vec<LevelJumpPad>,
+    #[serde(default)]
+    pub teleporters: Vec<LevelTeleporter>,
+    #[serde(default)]
+    pub boost_strips: Vec<LevelBoostStrip>,
+    /// Лифты, двери и прочая подвижная геометрия
+    #[serde(default)]
+    pub movers: Vec<LevelMover>,
+    #[serde(default)]
+    pub hazards: Vec<LevelHazard>,
+    /// Разрушаемые ящики и взрывающиеся бочки
+    #[serde(default)]
+    pub props: Vec<LevelProp>,
+    #[serde(default)]
+    pub triggers: Vec<LevelTrigger>,
+    /// Скрипты: условие и действия
+    #[serde(default)]
+    pub scripts: Vec<LevelScript>,
+}
+
+fn default_player_start() -> [f32; 3] {
+    [0.0, 3.0, 0.0]
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelMaterial {
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub emissive: [f32; 3],
+    #[serde(default)]
+    pub metallic: f32,
+    #[serde(default = "default_roughness")]
+    pub roughness: f32,
+}
+
+fn default_roughness() -> f32 {
+    0.5
+}
+
+/// Кусок статичной геометрии
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct Brush {
+    pub shape: BrushShape,
+    pub position: [f32; 3],
+    /// Поворот в градусах вокруг осей X, Y, Z
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    pub material: String,
+    /// Есть ли у фигуры коллайдер
+    #[serde(default = "default_solid")]
+    pub solid: bool,
+    /// Пол: по нему ходят враги (навигационная сетка) и на нем рисуется курсор
+    #[serde(default)]
+    pub ground: bool,
+}
+
+fn default_solid() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum BrushShape {
+    Box { size: [f32; 3] },
+    /// Плоскость с коллайдером-плитой толщиной 0.2
+    Plane { size: [f32; 2] },
+    Cylinder { radius: f32, height: f32 },
+}
+
+impl BrushShape {
+    fn mesh(&self) -> Mesh {
+        match *self {
+            BrushShape::Box { size } => Cuboid::new(size[0], size[1], size[2]).into(),
+            BrushShape::Plane { size } => Plane3d::default().mesh().size(size[0], size[1]).into(),
+            BrushShape::Cylinder { radius, height } => Cylinder::new(radius, height).into(),
+        }
+    }
+
+    fn collider(&self) -> Collider {
+        match *self {
+            BrushShape::Box { size } => Collider::cuboid(size[0] * 0.5, size[1] * 0.5, size[2] * 0.5),
+            BrushShape::Plane { size } => Collider::cuboid(size[0] * 0.5, 0.1, size[1] * 0.5),
+            BrushShape::Cylinder { radius, height } => Collider::cylinder(height * 0.5, radius),
+        }
+    }
+
+    /// Габариты фигуры
+    fn size(&self) -> Vec3 {
+        match *self {
+            BrushShape::Box { size } => Vec3::from(size),
+            BrushShape::Plane { size } => Vec3::new(size[0], 0.2, size[1]),
+            BrushShape::Cylinder { radius, height } => Vec3::new(radius * 2.0, height, radius * 2.0),
+        }
+    }
+
+    /// Коллайдер, уменьшенный на `margin` с каждой стороны
+    fn inset_collider(&self, margin: f32) -> Collider {
+        let inset = |value: f32| (value * 0.5 - margin).max(0.01);
+        match *self {
+            BrushShape::Box { size } => Collider::cuboid(inset(size[0]), inset(size[1]), inset(size[2])),
+            BrushShape::Plane { size } => Collider::cuboid(inset(size[0]), 0.01, inset(size[1])),
+            BrushShape::Cylinder { radius, height } => Collider::cylinder(inset(height), (radius - margin).max(0.01)),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelModelDefinition {
+    /// Путь к .gltf/.glb в assets; берется первая сцена файла
+    pub path: String,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Коллайдер для узлов, у которых нет своих настроек
+    #[serde(default)]
+    pub collider: ModelCollider,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelLight {
+    pub kind: LightKind,
+    pub position: [f32; 3],
+    /// Куда светит направленный свет
+    #[serde(default)]
+    pub look_at: [f32; 3],
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum LightKind {
+    Directional { illuminance: f32, #[serde(default)] shadows: bool },
+    Point { color: [f32; 3], intensity: f32, range: f32, #[serde(default)] shadows: bool },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelSpawnPoint {
+    pub position: [f32; 3],
+    pub group: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct LevelPickup {
+    pub item: String,
+    pub position: [f32; 3],
+    pub respawn_time: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelJumpPad {
+    pub position: [f32; 3],
+    /// Куда площадка забрасывает игрока
+    pub target: [f32; 3],
+    #[serde(default = "default_pad_radius")]
+    pub radius: f32,
+    #[serde(default)]
+    pub affects: Affects,
+}
+
+fn default_pad_radius() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelTeleporter {
+    pub position: [f32; 3],
+    /// Точка выхода - центр тела после переноса
+    pub destination: [f32; 3],
+    /// Куда смотрит выход, градусы вокруг вертикали (0 - на -Z)
+    #[serde(default)]
+    pub facing: Option<f32>,
+    #[serde(default = "default_keep_velocity")]
+    pub keep_velocity: bool,
+    #[serde(default = "default_pad_radius")]
+    pub radius: f32,
+    #[serde(default)]
+    pub affects: Affects,
+}
+
+fn default_keep_velocity() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelBoostStrip {
+    pub position: [f32; 3],
+    /// Ширина и длина полосы
+    pub size: [f32; 2],
+    /// Направление разгона, градусы вокруг вертикали (0 - на -Z)
+    #[serde(default)]
+    pub direction: f32,
+    pub speed: f32,
+    #[serde(default)]
+    pub affects: Affects,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelMover {
+    /// Имя, по которому на тело ссылаются скрипты уровня
+    #[serde(default)]
+    pub name: Option<String>,
+    pub shape: BrushShape,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    pub material: String,
+    pub waypoints: Vec<MoverWaypoint>,
+    #[serde(default)]
+    pub easing: Easing,
+    #[serde(default)]
+    pub mode: MoverMode,
+    #[serde(default)]
+    pub activation: MoverActivation,
+    #[serde(default)]
+    pub blocked: BlockedBehavior,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub enum MoverActivation {
+    /// Движется с самого начала
+    #[default]
+    Always,
+    /// Запускается, когда игрок входит в зону
+    Trigger { position: [f32; 3], half_extents: [f32; 3] },
+    /// Запускается кнопкой
+    Button { position: [f32; 3] },
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelHazard {
+    /// Центр нижней грани зоны
+    pub position: [f32; 3],
+    /// Ширина, высота и глубина зоны
+    pub size: [f32; 3],
+    pub effect: HazardEffect,
+    /// Без расписания зона работает всегда
+    #[serde(default)]
+    pub cycle: Option<HazardCycle>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelProp {
+    pub shape: BrushShape,
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    pub material: String,
+    pub health: f32,
+    /// Бочка: при разрушении взрывается
+    #[serde(default)]
+    pub explosion: Option<Explosion>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelScript {
+    /// Имя для журнала
+    #[serde(default)]
+    pub name: Option<String>,
+    pub trigger: ScriptCondition,
+    pub actions: Vec<ScriptAction>,
+    /// Срабатывает один раз; иначе - каждый раз, когда выполняется условие
+    #[serde(default = "default_once")]
+    pub once: bool,
+}
+
+fn default_once() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone)]
+pub enum LevelTrigger {
+    /// Запираемая арена босса, `boss` - путь к описанию босса
+    BossArena { boss: String, position: [f32; 3], half_extent: f32 },
+}
+
+/// Какой уровень сейчас загружен - по нему ведутся таблицы рекордов
+#[derive(Resource)]
+pub struct CurrentLevel {
+    pub name: String,
+    /// Зерно генератора, если уровень сгенерирован
+    pub seed: Option<u64>,
+    definition: Handle<LevelDefinition>,
+}
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_LEVEL.to_string(),
+            seed: None,
+            definition: Handle::default(),
+        }
+    }
+}
+
+impl CurrentLevel {
+    /// Начать загрузку уровня из файла. Уровень пересоздается при следующем входе в игру
+    pub fn load(&mut self, name: &str, asset_server: &AssetServer) {
+        self.name = name.to_string();
+        self.seed = None;
+        self.definition = asset_server.load(level_path(name));
+    }
+
+    /// Сделать текущим уже готовое описание, например сгенерированное по зерну
+    pub fn set_generated(&mut self, name: &str, seed: u64, definition: Handle<LevelDefinition>) {
+        self.name = name.to_string();
+        self.seed = Some(seed);
+        self.definition = definition;
+    }
+
+    pub fn definition<'a>(&self, levels: &'a Assets<LevelDefinition>) -> Option<&'a LevelDefinition> {
+        levels.get(&self.definition)
+    }
+
+    pub fn is_ready(&self, levels: &Assets<LevelDefinition>) -> bool {
+        levels.contains(&self.definition)
+    }
+
+    /// Точка появления игрока; пока уровень не загружен - центр арены
+    pub fn player_start(&self, levels: &Assets<LevelDefinition>) -> Vec3 {
+        Vec3::from(self.definition(levels).map_or_else(default_player_start, |level| level.player_start))
+    }
+}
+
+fn rotation_degrees([x, y, z]: [f32; 3]) -> Quat {
+    Quat::from_euler(EulerRot::XYZ, x.to_radians(), y.to_radians(), z.to_radians())
+}
+
+fn level_path(name: &str) -> String {
+    format!("levels/{}.level.ron", name)
+}
+
+fn load_initial_level(mut level: ResMut<CurrentLevel>, asset_server: Res<AssetServer>) {
+    let name = level.name.clone();
+    level.load(&name, &asset_server);
+}
+
+/// Уровень с ошибкой в файле не даст выйти из загрузки - возвращаемся к стандартному
+fn recover_failed_level(mut level: ResMut<CurrentLevel>, asset_server: Res<AssetServer>) {
+    if !asset_server.load_state(&level.definition).is_failed() {
+        return;
+    }
+    error!("Level '{}' failed to load", level.name);
+    if level.name != DEFAULT_LEVEL {
+        level.load(DEFAULT_LEVEL, &asset_server);
+    }
+}
+
+fn map_command(
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut level: ResMut<CurrentLevel>,
+    asset_server: Res<AssetServer>,
+    mut restarts: EventWriter<RestartLevel>,
+) {
+    for command in console_commands.read().filter(|command| command.name == "map") {
+        let Some(name) = command.args.first() else {
+            console.print(format!("Current map: {}", level.name));
+            continue;
+        };
+        if !Path::new("assets").join(level_path(name)).exists() {
+            console.print(format!("No such map '{}'", name));
+            continue;
+        }
+        console.print(format!("Loading map '{}'", name));
+        level.load(name, &asset_server);
+        restarts.send(RestartLevel);
+    }
+}
+
+pub fn spawn_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
+) {
+    let Some(definition) = level.definition(&levels) else {
+        error!("Level '{}' is not loaded", level.name);
+        return;
+    };
+
+    let level_materials: HashMap<&str, Handle<StandardMaterial>> = definition.materials
+        .iter()
+        .map(|(name, material)| {
+            let [r, g, b] = material.color;
+            let [er, eg, eb] = material.emissive;
+            let handle = materials.add(StandardMaterial {
+                base_color: Color::srgb(r, g, b),
+                emissive: LinearRgba::rgb(er, eg, eb),
+                metallic: material.metallic,
+                perceptual_roughness: material.roughness,
+                ..default()
+            });
+            (name.as_str(), handle)
+        })
+        .collect();
+
+    for brush in &definition.brushes {
+        let material = level_materials.get(brush.material.as_str()).cloned().unwrap_or_else(|| {
+            warn!("Level '{}': unknown material '{}'", level.name, brush.material);
+            Handle::default()
+        });
+        let mut entity = commands.spawn((
+            Mesh3d(meshes.add(brush.shape.mesh())),
+            MeshMaterial3d(material),
+            Transform::from_translation(Vec3::from(brush.position)).with_rotation(rotation_degrees(brush.rotation)),
+            Name::new("Brush"),
+            StateScoped(InGame),
+        ));
+        if brush.solid {
+            entity.insert((RigidBody::Fixed, brush.shape.collider()));
+        }
+        if brush.ground {
+            entity.insert((
+                Ground,
+                Friction {
+                    coefficient: 0.0, // Убираем трение для Quake-физики
+                    combine_rule: CoefficientCombineRule::Min,
+                },
+                Restitution {
+                    coefficient: 0.0, // Никакого отскока
+                    combine_rule: CoefficientCombineRule::Min,
+                },
+            ));
+        }
+    }
+
+    for model in &definition.models {
+        commands.spawn((
+            SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(model.path.clone()))),
+            Transform::from_translation(Vec3::from(model.position))
+                .with_rotation(rotation_degrees(model.rotation))
+                .with_scale(Vec3::splat(model.scale)),
+            LevelModel { collider: model.collider },
+            Name::new(format!("Model ({})", model.path)),
+            StateScoped(InGame),
+        ));
+    }
+
+    for light in &definition.lights {
+        let transform = Transform::from_translation(Vec3::from(light.position));
+        match light.kind {
+            LightKind::Directional { illuminance, shadows } => {
+                commands.spawn((
+                    DirectionalLight {
+                        shadows_enabled: shadows,
+                        illuminance,
+                        shadow_depth_bias: 0.02,
+                        shadow_normal_bias: 0.6,
+                        ..default()
+                    },
+                    transform.looking_at(Vec3::from(light.look_at), Vec3::Y),
+                    CascadeShadowConfig {
+                        minimum_distance: 0.1,
+                        bounds: vec![0.1, 5.0, 20.0, 100.0],
+                        overlap_proportion: 0.2,
+                    },
+                    Name::new("Directional Light"),
+                    StateScoped(InGame),
+                ));
+            }
+            LightKind::Point { color: [r, g, b], intensity, range, shadows } => {
+                commands.spawn((
+                    PointLight {
+                        color: Color::srgb(r, g, b),
+                        intensity,
+                        range,
+                        shadows_enabled: shadows,
+                        ..default()
+                    },
+                    transform,
+                    Name::new("Point Light"),
+                    StateScoped(InGame),
+                ));
+            }
+        }
+    }
+
+    for point in &definition.spawn_points {
+        commands.spawn((
+            Transform::from_translation(Vec3::from(point.position)),
+            EnemySpawnPoint { group: point.group.clone() },
+            Name::new("Enemy Spawn Point"),
+            StateScoped(InGame),
+        ));
+    }
+
+    for pickup in &definition.pickups {
+        commands.spawn((
+            Transform::from_translation(Vec3::from(pickup.position)),
+            PickupSpawner::new(&pickup.item, pickup.respawn_time),
+            Name::new("Pickup Point"),
+            StateScoped(InGame),
+        ));
+    }
+
+    let jump_pad_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.2, 0.8, 0.3),
+        emissive: LinearRgba::rgb(0.3, 1.5, 0.5),
+        ..default()
+    });
+    for pad in &definition.jump_pads {
+        commands.spawn((
+            Mesh3d(meshes.add(Cylinder::new(pad.radius, 0.1))),
+            MeshMaterial3d(jump_pad_material.clone()),
+            Transform::from_translation(Vec3::from(pad.position)),
+            // Сенсор выше самой площадки, чтобы ее задевали ноги игрока
+            Collider::cylinder(0.5, pad.radius),
+            Sensor,
+            ActiveCollisionTypes::all(),
+            JumpPad { target: Vec3::from(pad.target) },
+            pad.affects,
+            Name::new("Jump Pad"),
+            StateScoped(InGame),
+        ));
+    }
+
+    let teleporter_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.5, 0.2, 0.9),
+        emissive: LinearRgba::rgb(1.0, 0.3, 2.0),
+        ..default()
+    });
+    for teleporter in &definition.teleporters {
+        let disc = meshes.add(Cylinder::new(teleporter.radius, 0.1));
+        commands.spawn((
+            Mesh3d(disc.clone()),
+            MeshMaterial3d(teleporter_material.clone()),
+            Transform::from_translation(Vec3::from(teleporter.position)),
+            Collider::cylinder(0.5, teleporter.radius),
+            Sensor,
+            ActiveCollisionTypes::all(),
+            Teleporter {
+                destination: Vec3::from(teleporter.destination),
+                facing: teleporter.facing.map(f32::to_radians),
+                keep_velocity: teleporter.keep_velocity,
+            },
+            teleporter.affects,
+            Name::new("Teleporter"),
+            StateScoped(InGame),
+        ));
+        // Метка выхода на полу под точкой назначения, без коллайдера
+        let [x, y, z] = teleporter.destination;
+        commands.spawn((
+            Mesh3d(disc),
+            MeshMaterial3d(teleporter_material.clone()),
+            Transform::from_xyz(x, y - 0.9, z).with_scale(Vec3::new(0.6, 1.0, 0.6)),
+            Name::new("Teleporter Exit"),
+            StateScoped(InGame),
+        ));
+    }
+
+    let boost_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.6, 0.1),
+        emissive: LinearRgba::rgb(2.0, 1.0, 0.1),
+        ..default()
+    });
+    for strip in &definition.boost_strips {
+        let [width, length] = strip.size;
+        let rotation = Quat::from_rotation_y(strip.direction.to_radians());
+        commands.spawn((
+            Mesh3d(meshes.add(Cuboid::new(width, 0.05, length))),
+            MeshMaterial3d(boost_material.clone()),
+            Transform::from_translation(Vec3::from(strip.position)).with_rotation(rotation),
+            Collider::cuboid(width * 0.5, 0.5, length * 0.5),
+            Sensor,
+            ActiveCollisionTypes::all(),
+            BoostStrip { direction: rotation * Vec3::NEG_Z, speed: strip.speed },
+            strip.affects,
+            Name::new("Boost Strip"),
+            StateScoped(InGame),
+        ));
+    }
+
+    let button_material = materials.add(StandardMaterial {
+        base_color: Color::srgb(0.9, 0.1, 0.1),
+        emissive: LinearRgba::rgb(1.5, 0.1, 0.1),
+        ..default()
+    });
+    for (index, mover) in definition.movers.iter().enumerate() {
+        let material = level_materials.get(mover.material.as_str()).cloned().unwrap_or_else(|| {
+            warn!("Level '{}': unknown material '{}'", level.name, mover.material);
+            Handle::default()
+        });
+        if mover.waypoints.len() < 2 {
+            warn!("Level '{}': mover {:?} needs at least two waypoints", level.name, mover.name);
+        }
+        let transform = Transform::from_translation(Vec3::from(mover.position)).with_rotation(rotation_degrees(mover.rotation));
+        let entity = commands
+            .spawn((
+                Mesh3d(meshes.add(mover.shape.mesh())),
+                MeshMaterial3d(material),
+                transform,
+                RigidBody::KinematicPositionBased,
+                mover.shape.collider(),
+                Mover::new(
+                    index,
+                    transform,
+                    mover.waypoints.clone(),
+                    mover.easing,
+                    mover.mode,
+                    mover.blocked,
+                    mover.shape.inset_collider(MOVER_PROBE_MARGIN),
+                    matches!(mover.activation, MoverActivation::Always),
+                ),
+                Name::new(mover.name.clone().unwrap_or_else(|| "Mover".to_string())),
+                StateScoped(InGame),
+            ))
+            .id();
+        match mover.activation {
+            MoverActivation::Always => {}
+            MoverActivation::Trigger { position, half_extents: [x, y, z] } => {
+                commands.spawn((
+                    Transform::from_translation(Vec3::from(position)),
+                    Collider::cuboid(x, y, z),
+                    Sensor,
+                    ActiveCollisionTypes::all(),
+                    MoverTrigger { mover: entity },
+                    Name::new("Mover Trigger"),
+                    StateScoped(InGame),
+                ));
+            }
+            MoverActivation::Button { position } => {
+                commands.spawn((
+                    Mesh3d(meshes.add(Cuboid::new(0.3, 0.3, 0.3))),
+                    MeshMaterial3d(button_material.clone()),
+                    Transform::from_translation(Vec3::from(position)),
+                    MoverButton { mover: entity },
+                    Name::new("Mover Button"),
+                    StateScoped(InGame),
+                ));
+            }
+        }
+    }
+
+    for hazard in &definition.hazards {
+        let [width, height, depth] = hazard.size;
+        let (base_color, emissive) = hazard.effect.color();
+        let mut entity = commands.spawn((
+            Transform::from_translation(Vec3::from(hazard.position) + Vec3::Y * height * 0.5),
+            Visibility::default(),
+            Collider::cuboid(width * 0.5, height * 0.5, depth * 0.5),
+            Sensor,
+            ActiveCollisionTypes::all(),
+            HazardZone::new(hazard.effect),
+            Name::new("Hazard"),
+            StateScoped(InGame),
+        ));
+        if let Some(cycle) = hazard.cycle {
+            entity.insert(cycle);
+        }
+        // Видна только нижняя грань зоны - лужа, решетка, лента
+        entity.with_child((
+            Mesh3d(meshes.add(Cuboid::new(width, 0.04, depth))),
+            MeshMaterial3d(materials.add(StandardMaterial { base_color, emissive, ..default() })),
+            Transform::from_xyz(0.0, 0.02 - height * 0.5, 0.0),
+        ));
+    }
+
+    for (index, prop) in definition.props.iter().enumerate() {
+        let material = level_materials.get(prop.material.as_str()).cloned().unwrap_or_else(|| {
+            warn!("Level '{}': unknown material '{}'", level.name, prop.material);
+            Handle::default()
+        });
+        // Цвет обломков - цвет материала предмета
+        let color = definition.materials.get(&prop.material).map_or(Color::WHITE, |material| {
+            let [r, g, b] = material.color;
+            Color::srgb(r, g, b)
+        });
+        commands.spawn((
+            Mesh3d(meshes.add(prop.shape.mesh())),
+            MeshMaterial3d(material),
+            Transform::from_translation(Vec3::from(prop.position)).with_rotation(rotation_degrees(prop.rotation)),
+            // Неподвижная геометрия - попадает в навигационную сетку как обычное укрытие
+            RigidBody::Fixed,
+            prop.shape.collider(),
+            Health::new(prop.health),
+            Faction::Neutral,
+            Destructible { index, size: prop.shape.size(), color, explosion: prop.explosion },
+            Name::new(if prop.explosion.is_some() { "Explosive Barrel" } else { "Prop" }),
+            StateScoped(InGame),
+        ));
+    }
+
+    for trigger in &definition.triggers {
+        match trigger {
+            LevelTrigger::BossArena { boss, position, half_extent } => {
+                commands.spawn((
+                    Transform::from_translation(Vec3::from(*position)),
+                    BossArenaTrigger::new(asset_server.load(boss), *half_extent),
+                    Name::new("Boss Arena"),
+                    StateScoped(InGame),
+                ));
+            }
+        }
+    }
+
+    for (index, script) in definition.scripts.iter().enumerate() {
+        if script.actions.iter().any(|action| matches!(action, ScriptAction::Delay(_))) {
+            warn!("Level '{}': script {:?} has a Delay outside of a Sequence, it does nothing", level.name, script.name);
+        }
+        let mut entity = commands.spawn((
+            ScriptTrigger::new(index, script.trigger.clone(), script.actions.clone(), script.once),
+            Name::new(script.name.clone().unwrap_or_else(|| "Script".to_string())),
+            StateScoped(InGame),
+        ));
+        if let Some((position, [x, y, z])) = script.trigger.volume() {
+            entity.insert((
+                Transform::from_translation(Vec3::from(position)),
+                Collider::cuboid(x, y, z),
+                Sensor,
+                ActiveCollisionTypes::all(),
+            ));
+        }
+    }
+
+    info!("Level '{}' built: {} brushes", level.name, definition.brushes.len());
+}
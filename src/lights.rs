@@ -1,15 +1,12 @@
-use bevy::{
-    prelude::*,
-    pbr::CascadeShadowConfig,
-};
+// Солнце и прочий свет уровня описаны в файле уровня (level.rs)
+use bevy::prelude::*;
 
 use crate::player::Player;
 
 pub struct LightsPlugin;
 impl Plugin for LightsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_directional_light)
-           .add_systems(Update, spawn_spotlight);
+        app.add_systems(Update, spawn_spotlight);
     }
 }
 
@@ -17,25 +14,6 @@ impl Plugin for LightsPlugin {
 #[derive(Component)]
 struct FlashlightEquipped; 
 
-fn spawn_directional_light(mut commands: Commands) {
-    commands.spawn((
-        DirectionalLight {
-            shadows_enabled: true,
-            illuminance: 50.0, // Примечание: 50.0 люкс для солнца маловато, обычно ~10000+
-            shadow_depth_bias: 0.02,
-            shadow_normal_bias: 0.6,
-            ..default()
-        },
-        Transform::from_xyz(14.0, 10.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
-        CascadeShadowConfig {
-            minimum_distance: 0.1,
-            bounds: vec![0.1, 5.0, 20.0, 100.0],
-            overlap_proportion: 0.2,
-        },
-        Name::new("Directional Light")
-    ));
-}
-
 fn spawn_spotlight(
     mut commands: Commands, 
     // 2. Ищем игрока БЕЗ маркера FlashlightEquipped
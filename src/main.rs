@@ -1,4 +1,8 @@
 // src/main.rs - Обновленный main с новыми системами
+// Запросы Bevy с фильтрами и системы с множеством параметров -
+// обычное дело, clippy на них не ругаем
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
 use bevy::{
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin}, 
     prelude::*,
@@ -11,6 +15,32 @@ mod camera;
 mod world;
 mod weapons;
 mod enemies;
+mod navigation;
+mod perception;
+mod crowd;
+mod data;
+mod waves;
+mod archetypes;
+mod melee;
+mod boss;
+mod ui;
+mod death;
+mod pickups;
+mod powerups;
+mod stats;
+mod state;
+mod menu;
+mod score;
+mod highscores;
+mod savegame;
+mod console;
+mod level;
+mod models;
+mod procgen;
+mod movers;
+mod hazards;
+mod props;
+mod scripts;
 
 use bevy_rapier3d::prelude::*;
 //use bevy_inspector_egui::quick::WorldInspectorPlugin;
@@ -20,15 +50,63 @@ use world::WorldPlugin;
 use lights::LightsPlugin;
 use weapons::{WeaponsPlugin, equip_player_weapon};
 use enemies::EnemiesPlugin;
+use navigation::NavigationPlugin;
+use perception::PerceptionPlugin;
+use crowd::CrowdPlugin;
+use waves::WavesPlugin;
+use archetypes::ArchetypesPlugin;
+use melee::MeleePlugin;
+use boss::BossPlugin;
+use ui::UIPlugin;
+use death::DeathPlugin;
+use pickups::PickupsPlugin;
+use powerups::PowerUpsPlugin;
+use stats::StatsPlugin;
+use state::{GameState, GameStatePlugin};
+use menu::MenuPlugin;
+use score::ScorePlugin;
+use highscores::HighScoresPlugin;
+use savegame::SaveGamePlugin;
+use console::ConsolePlugin;
+use level::LevelPlugin;
+use models::ModelsPlugin;
+use procgen::ProcGenPlugin;
+use movers::MoversPlugin;
+use hazards::HazardsPlugin;
+use props::PropsPlugin;
+use scripts::ScriptsPlugin;
 
 fn main() {
     App::new()
         .add_plugins(PlayerPlugin)
         .add_plugins(CameraPlugin)
         .add_plugins(WorldPlugin)
+        .add_plugins(LevelPlugin)
+        .add_plugins(ModelsPlugin)
+        .add_plugins(ProcGenPlugin)
+        .add_plugins(MoversPlugin)
+        .add_plugins(HazardsPlugin)
+        .add_plugins(PropsPlugin)
+        .add_plugins(ScriptsPlugin)
         .add_plugins(LightsPlugin)
         .add_plugins(WeaponsPlugin)
         .add_plugins(EnemiesPlugin)
+        .add_plugins(NavigationPlugin)
+        .add_plugins(PerceptionPlugin)
+        .add_plugins(CrowdPlugin)
+        .add_plugins(WavesPlugin)
+        .add_plugins(ArchetypesPlugin)
+        .add_plugins(MeleePlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(UIPlugin)
+        .add_plugins(DeathPlugin)
+        .add_plugins(PickupsPlugin)
+        .add_plugins(PowerUpsPlugin)
+        .add_plugins(ScorePlugin)
+        .add_plugins(HighScoresPlugin)
+        .add_plugins(SaveGamePlugin)
+        .add_plugins(ConsolePlugin)
+        .add_plugins(StatsPlugin)
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
                 mode: WindowMode::Windowed,
@@ -41,6 +119,9 @@ fn main() {
             RapierDebugRenderPlugin::default(),
             RapierPhysicsPlugin::<NoUserData>::default(),
         ))
+        // Состояния игры - после DefaultPlugins, которые регистрируют StatesPlugin
+        .add_plugins(GameStatePlugin)
+        .add_plugins(MenuPlugin)
         //.add_plugins(WorldInspectorPlugin::new())
         .add_plugins((
             LogDiagnosticsPlugin::default(),
@@ -49,20 +130,10 @@ fn main() {
         .add_systems(Startup, setup)
         .add_systems(Update, (
             equip_player_weapon, // Добавляем оружие игроку после спавна
-            check_exit,
-        ))
+        ).run_if(in_state(GameState::Playing)))
         .run();
 }
 
 fn setup() {
     // Здесь будет код инициализации
 }
-
-fn check_exit(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut app_exit_events: EventWriter<AppExit>,
-) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
-        app_exit_events.send(AppExit::default());
-    }
-}
@@ -0,0 +1,318 @@
+// src/melee.rs - Ближний бой врагов: замах, активный хитбокс, восстановление и перезарядка.
+// Камикадзе вместо удара подрываются рядом с игроком
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::enemies::{Health, Hit};
+use crate::perception::{Perception, PerceptionSet};
+use crate::player::Player;
+use crate::props::{Detonation, Explosion};
+use crate::weapons::Faction;
+use crate::stats::{Stat, Stats};
+use crate::state::{GameState, InGame};
+
+pub struct MeleePlugin;
+impl Plugin for MeleePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+               start_melee_attacks,
+               tick_melee_attacks,
+               melee_hit_system,
+               update_melee_telegraph,
+               cleanup_orphan_hitboxes,
+               detonate_exploders,
+           ).chain().after(PerceptionSet).run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MeleePhase {
+    Ready,
+    Windup,
+    Active,
+    Recovery,
+    Cooldown,
+}
+
+/// Удар вблизи. Замах заметен визуально, урон наносится только в активные кадры
+/// сенсором перед атакующим, поэтому от удара можно увернуться
+#[derive(Component)]
+pub struct MeleeAttack {
+    pub damage: f32,
+    pub range: f32,          // Начинаем замах, если цель ближе
+    pub windup: f32,         // Длительность фаз (сек)
+    pub active: f32,
+    pub recovery: f32,
+    pub cooldown: f32,
+    pub hitbox_radius: f32,
+    pub phase: MeleePhase,
+    timer: f32,
+    direction: Vec3,         // Направление удара фиксируется в начале замаха
+    hitbox: Option<Entity>,
+}
+
+impl MeleeAttack {
+    pub fn new(damage: f32, range: f32, windup: f32, active: f32, recovery: f32, cooldown: f32) -> Self {
+        Self {
+            damage,
+            range,
+            windup,
+            active,
+            recovery,
+            cooldown,
+            hitbox_radius: (range * 0.5).max(0.5),
+            phase: MeleePhase::Ready,
+            timer: 0.0,
+            direction: Vec3::NEG_Z,
+            hitbox: None,
+        }
+    }
+
+    /// Во время атаки враг стоит на месте
+    pub fn is_attacking(&self) -> bool {
+        matches!(self.phase, MeleePhase::Windup | MeleePhase::Active | MeleePhase::Recovery)
+    }
+}
+
+// Радиус взрыва камикадзе относительно дистанции подрыва
+const EXPLODE_RADIUS_MULTIPLIER: f32 = 2.0;
+const EXPLODE_PUSH: f32 = 8.0;
+
+/// Камикадзе: подрывается, когда игрок ближе `range`, и погибает сам
+#[derive(Component)]
+pub struct ExplodeAttack {
+    pub damage: f32,
+    pub range: f32,
+}
+
+/// Сенсор удара, живет только в активной фазе
+#[derive(Component)]
+pub struct MeleeHitbox {
+    pub owner: Entity,
+    pub damage: f32,
+    pub faction: Faction,
+    pub direction: Vec3,
+    hit: Vec<Entity>, // Каждую цель бьем не больше одного раза за удар
+}
+
+/// Подсветка замаха: на время атаки враг получает собственную копию материала
+/// и приседает перед ударом - заметно и на темных, и на светящихся врагах
+#[derive(Component)]
+struct MeleeTelegraph {
+    original: Handle<StandardMaterial>,
+    scale: Vec3,
+}
+
+// Насколько враг сжимается по высоте к концу замаха и вытягивается в ударе
+const WINDUP_SQUASH: f32 = 0.25;
+const STRIKE_STRETCH: f32 = 0.15;
+
+fn start_melee_attacks(
+    player_query: Query<&Transform, With<Player>>,
+    mut attackers: Query<(&Transform, &Perception, &mut MeleeAttack)>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    for (transform, perception, mut attack) in attackers.iter_mut() {
+        if attack.phase != MeleePhase::Ready || !perception.sees_target {
+            continue;
+        }
+        let offset = player_transform.translation - transform.translation;
+        let horizontal = Vec3::new(offset.x, 0.0, offset.z);
+        if horizontal.length() > attack.range {
+            continue;
+        }
+        attack.direction = horizontal.normalize_or(Vec3::NEG_Z);
+        attack.phase = MeleePhase::Windup;
+        attack.timer = attack.windup;
+    }
+}
+
+fn tick_melee_attacks(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut attackers: Query<(Entity, &Transform, &Faction, &mut MeleeAttack, Option<&Stats>)>,
+) {
+    for (entity, transform, faction, mut attack, stats) in attackers.iter_mut() {
+        if attack.phase == MeleePhase::Ready {
+            continue;
+        }
+        attack.timer -= time.delta_secs();
+        if attack.timer > 0.0 {
+            continue;
+        }
+
+        match attack.phase {
+            MeleePhase::Windup => {
+                // Удар: сенсор перед атакующим в зафиксированном направлении
+                let position = transform.translation + attack.direction * attack.range * 0.6;
+                let hitbox = commands.spawn((
+                    Transform::from_translation(position),
+                    Collider::ball(attack.hitbox_radius),
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                    // Сенсор без тела считается статичным - разрешаем ему пересекаться с кинематическим игроком
+                    ActiveCollisionTypes::all(),
+                    MeleeHitbox {
+                        owner: entity,
+                        damage: stats.map_or(attack.damage, |stats| stats.apply(Stat::Damage, attack.damage)),
+                        faction: *faction,
+                        direction: attack.direction,
+                        hit: Vec::new(),
+                    },
+                    Name::new("Melee Hitbox"),
+                    StateScoped(InGame),
+                )).id();
+                attack.hitbox = Some(hitbox);
+                attack.phase = MeleePhase::Active;
+                attack.timer = attack.active;
+            }
+            MeleePhase::Active => {
+                if let Some(hitbox) = attack.hitbox.take() {
+                    commands.entity(hitbox).despawn();
+                }
+                attack.phase = MeleePhase::Recovery;
+                attack.timer = attack.recovery;
+            }
+            MeleePhase::Recovery => {
+                attack.phase = MeleePhase::Cooldown;
+                attack.timer = attack.cooldown;
+            }
+            MeleePhase::Cooldown => {
+                attack.phase = MeleePhase::Ready;
+            }
+            MeleePhase::Ready => {}
+        }
+    }
+}
+
+fn melee_hit_system(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut hitboxes: Query<&mut MeleeHitbox>,
+    mut targets: Query<(&mut Health, &Faction)>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = collision_event else {
+            continue;
+        };
+        let (hitbox_entity, target_entity) = if hitboxes.contains(*entity1) {
+            (*entity1, *entity2)
+        } else if hitboxes.contains(*entity2) {
+            (*entity2, *entity1)
+        } else {
+            continue;
+        };
+
+        let Ok(mut hitbox) = hitboxes.get_mut(hitbox_entity) else {
+            continue;
+        };
+        if target_entity == hitbox.owner || hitbox.hit.contains(&target_entity) {
+            continue;
+        }
+        let Ok((mut health, faction)) = targets.get_mut(target_entity) else {
+            continue;
+        };
+        if *faction == hitbox.faction {
+            continue;
+        }
+
+        health.take_damage(hitbox.damage, Hit {
+            source: Some(hitbox.owner),
+            impulse: hitbox.direction * hitbox.damage * 0.15,
+        });
+        hitbox.hit.push(target_entity);
+    }
+}
+
+fn update_melee_telegraph(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut attackers: Query<(Entity, &MeleeAttack, &mut MeshMaterial3d<StandardMaterial>, &mut Transform, Option<&MeleeTelegraph>)>,
+) {
+    for (entity, attack, mut material, mut transform, telegraph) in attackers.iter_mut() {
+        match (attack.phase, telegraph) {
+            (MeleePhase::Windup | MeleePhase::Active, None) => {
+                // Начало замаха - заводим свою копию материала, чтобы не мигали все враги этого типа
+                let Some(copy) = materials.get(&material.0).cloned() else {
+                    continue;
+                };
+                // Масштаб меняется только у видимой части - коллайдер остается прежним
+                commands.entity(entity).insert((
+                    MeleeTelegraph { original: material.0.clone(), scale: transform.scale },
+                    ColliderScale::Absolute(transform.scale),
+                ));
+                material.0 = materials.add(copy);
+            }
+            (MeleePhase::Windup, Some(telegraph)) => {
+                // Мигаем все быстрее по мере приближения удара
+                let progress = 1.0 - (attack.timer / attack.windup.max(f32::EPSILON)).clamp(0.0, 1.0);
+                let pulse = (time.elapsed_secs() * (10.0 + 30.0 * progress)).sin() * 0.5 + 0.5;
+                if let Some(flash) = materials.get_mut(&material.0) {
+                    flash.emissive = LinearRgba::rgb(2.0, 1.2, 0.2) * (pulse * (0.5 + progress));
+                }
+                // Приседаем, раздаваясь вширь, - как перед прыжком
+                let squash = WINDUP_SQUASH * progress;
+                transform.scale = telegraph.scale * Vec3::new(1.0 + squash * 0.5, 1.0 - squash, 1.0 + squash * 0.5);
+            }
+            (MeleePhase::Active, Some(telegraph)) => {
+                if let Some(flash) = materials.get_mut(&material.0) {
+                    flash.emissive = LinearRgba::rgb(4.0, 0.5, 0.2);
+                }
+                transform.scale = telegraph.scale * Vec3::new(1.0 - STRIKE_STRETCH * 0.5, 1.0 + STRIKE_STRETCH, 1.0 - STRIKE_STRETCH * 0.5);
+            }
+            (MeleePhase::Recovery | MeleePhase::Cooldown | MeleePhase::Ready, Some(telegraph)) => {
+                // Возвращаем общий материал типа, копию удаляем
+                materials.remove(&material.0);
+                material.0 = telegraph.original.clone();
+                transform.scale = telegraph.scale;
+                commands.entity(entity)
+                    .remove::<MeleeTelegraph>()
+                    .insert(ColliderScale::Relative(Vec3::ONE));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Атакующий погиб посреди удара - убираем его хитбокс
+fn cleanup_orphan_hitboxes(
+    mut commands: Commands,
+    hitboxes: Query<(Entity, &MeleeHitbox)>,
+    attackers: Query<(), With<MeleeAttack>>,
+) {
+    for (entity, hitbox) in hitboxes.iter() {
+        if !attackers.contains(hitbox.owner) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Подрыв идет обычным взрывом (`Detonation`), а сам враг погибает
+/// обычной смертью - без очков игроку
+fn detonate_exploders(
+    player_query: Query<&Transform, With<Player>>,
+    mut exploders: Query<(Entity, &Transform, &ExplodeAttack, &mut Health, Option<&Stats>)>,
+    mut detonations: EventWriter<Detonation>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    for (entity, transform, attack, mut health, stats) in exploders.iter_mut() {
+        if health.is_dead() || transform.translation.distance(player_transform.translation) > attack.range {
+            continue;
+        }
+        detonations.send(Detonation {
+            position: transform.translation,
+            explosion: Explosion {
+                damage: stats.map_or(attack.damage, |stats| stats.apply(Stat::Damage, attack.damage)),
+                radius: attack.range * EXPLODE_RADIUS_MULTIPLIER,
+                push: EXPLODE_PUSH,
+            },
+            source: Some(entity),
+        });
+        let current = health.current;
+        health.take_piercing_damage(current, Hit { source: None, impulse: Vec3::ZERO });
+    }
+}
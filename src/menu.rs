@@ -0,0 +1,590 @@
+// src/menu.rs - Главное меню, меню паузы и страницы настроек.
+// Управляется клавиатурой, мышью и геймпадом. Настройки не дублируются: каждая
+// читает и меняет свой ресурс (окно, громкость, сложность, лимит обломков)
+use bevy::audio::{GlobalVolume, Volume};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use crate::death::DebrisBudget;
+use crate::player::{Binding, Control, KeyBindings};
+use crate::state::{GameState, RestartLevel};
+use crate::stats::Difficulty;
+
+pub struct MenuPlugin;
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Menu>()
+           .add_event::<MenuInput>()
+           .add_systems(OnEnter(GameState::MainMenu), open_menu(MenuPage::Main))
+           .add_systems(OnEnter(GameState::Paused), open_menu(MenuPage::Pause))
+           .add_systems(OnExit(GameState::MainMenu), close_menu)
+           .add_systems(OnExit(GameState::Paused), close_menu)
+           .add_systems(Update, (
+               read_menu_input,
+               capture_binding,
+               apply_menu_input,
+               rebuild_menu.run_if(resource_changed::<Menu>),
+           ).chain());
+    }
+}
+
+// Остальные клавиши, которые показывает страница управления; движение и огонь переназначаются
+const CONTROLS: &[(&str, &str)] = &[
+    ("Use", "E"),
+    ("Aim", "Mouse cursor"),
+    ("Pause", "Esc / Start"),
+    ("Unstuck", "R"),
+    ("Cycle difficulty", "F4"),
+    ("Quick save", "F5"),
+    ("Quick load", "F9"),
+    ("Console", "`"),
+    ("Navigation debug", "F3"),
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuPage {
+    Main,
+    Pause,
+    Settings,
+    Video,
+    Audio,
+    Controls,
+    Gameplay,
+}
+
+impl MenuPage {
+    fn title(&self) -> &'static str {
+        match self {
+            MenuPage::Main => "ARENA",
+            MenuPage::Pause => "PAUSED",
+            MenuPage::Settings => "SETTINGS",
+            MenuPage::Video => "VIDEO",
+            MenuPage::Audio => "AUDIO",
+            MenuPage::Controls => "CONTROLS",
+            MenuPage::Gameplay => "GAMEPLAY",
+        }
+    }
+
+    fn items(&self) -> Vec<MenuAction> {
+        match self {
+            MenuPage::Main => vec![
+                MenuAction::Play,
+                MenuAction::Open(MenuPage::Settings),
+                MenuAction::Quit,
+            ],
+            MenuPage::Pause => vec![
+                MenuAction::Resume,
+                MenuAction::Restart,
+                MenuAction::Open(MenuPage::Settings),
+                MenuAction::QuitToMenu,
+            ],
+            MenuPage::Settings => vec![
+                MenuAction::Open(MenuPage::Video),
+                MenuAction::Open(MenuPage::Audio),
+                MenuAction::Open(MenuPage::Controls),
+                MenuAction::Open(MenuPage::Gameplay),
+                MenuAction::Back,
+            ],
+            MenuPage::Video => vec![
+                MenuAction::Setting(Setting::WindowMode),
+                MenuAction::Setting(Setting::VSync),
+                MenuAction::Back,
+            ],
+            MenuPage::Audio => vec![
+                MenuAction::Setting(Setting::MasterVolume),
+                MenuAction::Back,
+            ],
+            MenuPage::Controls => Control::ALL
+                .into_iter()
+                .map(|control| MenuAction::Setting(Setting::Binding(control)))
+                .chain([MenuAction::Setting(Setting::StickDeadzone), MenuAction::Back])
+                .collect(),
+            MenuPage::Gameplay => vec![
+                MenuAction::Setting(Setting::Difficulty),
+                MenuAction::Setting(Setting::DebrisLimit),
+                MenuAction::Back,
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuAction {
+    Play,
+    Resume,
+    Restart,
+    Open(MenuPage),
+    Back,
+    QuitToMenu,
+    Quit,
+    /// Значение меняется влево/вправо, подтверждение листает вперед
+    Setting(Setting),
+}
+
+impl MenuAction {
+    fn label(&self) -> &'static str {
+        match self {
+            MenuAction::Play => "Play",
+            MenuAction::Resume => "Resume",
+            MenuAction::Restart => "Restart",
+            MenuAction::Open(MenuPage::Settings) => "Settings",
+            MenuAction::Open(MenuPage::Video) => "Video",
+            MenuAction::Open(MenuPage::Audio) => "Audio",
+            MenuAction::Open(MenuPage::Controls) => "Controls",
+            MenuAction::Open(MenuPage::Gameplay) => "Gameplay",
+            MenuAction::Open(_) => "",
+            MenuAction::Back => "Back",
+            MenuAction::QuitToMenu => "Quit to Menu",
+            MenuAction::Quit => "Quit",
+            MenuAction::Setting(setting) => setting.label(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Setting {
+    WindowMode,
+    VSync,
+    MasterVolume,
+    StickDeadzone,
+    Difficulty,
+    DebrisLimit,
+    /// Подтверждение ждет новую клавишу для действия
+    Binding(Control),
+}
+
+impl Setting {
+    fn label(&self) -> &'static str {
+        match self {
+            Setting::WindowMode => "Window mode",
+            Setting::VSync => "VSync",
+            Setting::MasterVolume => "Master volume",
+            Setting::StickDeadzone => "Stick deadzone",
+            Setting::Difficulty => "Difficulty",
+            Setting::DebrisLimit => "Debris limit",
+            Setting::Binding(control) => control.label(),
+        }
+    }
+}
+
+/// Стек открытых страниц: видна верхняя, Back возвращает к предыдущей
+#[derive(Resource)]
+struct Menu {
+    stack: Vec<(MenuPage, usize)>, // Страница и выбранный на ней пункт
+    stick_deadzone: f32,           // Порог стика геймпада для навигации по меню
+    rebinding: Option<Control>,    // Действие, которому ждем новую клавишу
+}
+
+impl Default for Menu {
+    fn default() -> Self {
+        Self {
+            stack: Vec::new(),
+            stick_deadzone: 0.5,
+            rebinding: None,
+        }
+    }
+}
+
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Hover(usize),
+    Click(usize),
+}
+
+#[derive(Component)]
+struct MenuRoot;
+
+#[derive(Component)]
+struct MenuItem(usize);
+
+/// Ресурсы, которые меняют страницы настроек
+#[derive(SystemParam)]
+struct Settings<'w, 's> {
+    windows: Query<'w, 's, &'static mut Window, With<PrimaryWindow>>,
+    volume: ResMut<'w, GlobalVolume>,
+    difficulty: ResMut<'w, Difficulty>,
+    debris: ResMut<'w, DebrisBudget>,
+    bindings: ResMut<'w, KeyBindings>,
+}
+
+impl Settings<'_, '_> {
+    fn value(&self, setting: Setting, menu: &Menu) -> String {
+        match setting {
+            Setting::WindowMode => match self.windows.get_single().map(|window| window.mode) {
+                Ok(WindowMode::BorderlessFullscreen(_)) => "Borderless".to_string(),
+                Ok(WindowMode::Fullscreen(_) | WindowMode::SizedFullscreen(_)) => "Fullscreen".to_string(),
+                _ => "Windowed".to_string(),
+            },
+            Setting::VSync => {
+                let vsync = self.windows.get_single().is_ok_and(|window| window.present_mode == PresentMode::AutoVsync);
+                if vsync { "On" } else { "Off" }.to_string()
+            }
+            Setting::MasterVolume => format!("{:.0}%", self.volume.volume.get() * 100.0),
+            Setting::StickDeadzone => format!("{:.1}", menu.stick_deadzone),
+            Setting::Difficulty => format!("{:?}", *self.difficulty),
+            Setting::DebrisLimit => self.debris.max_pieces.to_string(),
+            Setting::Binding(control) if menu.rebinding == Some(control) => "Press a key...".to_string(),
+            Setting::Binding(control) => self.bindings.get(control).label(),
+        }
+    }
+
+    /// Шаг значения: `step` = -1 влево, +1 вправо. Списки листаются по кругу, числа упираются в пределы
+    fn adjust(&mut self, setting: Setting, step: i32, menu: &mut Menu) {
+        match setting {
+            Setting::WindowMode => {
+                let Ok(mut window) = self.windows.get_single_mut() else {
+                    return;
+                };
+                const MODES: [WindowMode; 3] = [
+                    WindowMode::Windowed,
+                    WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+                    WindowMode::Fullscreen(MonitorSelection::Current),
+                ];
+                let current = match window.mode {
+                    WindowMode::Windowed => 0,
+                    WindowMode::BorderlessFullscreen(_) => 1,
+                    _ => 2,
+                };
+                window.mode = MODES[cycle(current, step, MODES.len())];
+            }
+            Setting::VSync => {
+                if let Ok(mut window) = self.windows.get_single_mut() {
+                    window.present_mode = match window.present_mode {
+                        PresentMode::AutoVsync => PresentMode::AutoNoVsync,
+                        _ => PresentMode::AutoVsync,
+                    };
+                }
+            }
+            Setting::MasterVolume => {
+                let volume = (self.volume.volume.get() + 0.1 * step as f32).clamp(0.0, 1.0);
+                self.volume.volume = Volume::new(volume);
+            }
+            Setting::StickDeadzone => {
+                menu.stick_deadzone = (menu.stick_deadzone + 0.1 * step as f32).clamp(0.2, 0.8);
+            }
+            Setting::Difficulty => {
+                const LEVELS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+                let current = LEVELS.iter().position(|level| *level == *self.difficulty).unwrap_or(1);
+                *self.difficulty = LEVELS[cycle(current, step, LEVELS.len())];
+            }
+            Setting::DebrisLimit => {
+                let pieces = self.debris.max_pieces as i32 + 40 * step;
+                self.debris.max_pieces = pieces.clamp(40, 400) as usize;
+            }
+            // Клавиша назначается нажатием, влево/вправо ее не листают
+            Setting::Binding(_) => {}
+        }
+    }
+}
+
+fn select(menu: &mut Menu, index: usize) {
+    if let Some(top) = menu.stack.last_mut() {
+        top.1 = index;
+    }
+}
+
+fn cycle(index: usize, step: i32, len: usize) -> usize {
+    (index as i32 + step).rem_euclid(len as i32) as usize
+}
+
+fn open_menu(page: MenuPage) -> impl FnMut(ResMut<Menu>) {
+    move |mut menu: ResMut<Menu>| {
+        menu.stack = vec![(page, 0)];
+    }
+}
+
+fn close_menu(mut menu: ResMut<Menu>) {
+    menu.stack.clear();
+}
+
+/// Собирает ввод со всех устройств в общие события меню
+fn read_menu_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    menu: Res<Menu>,
+    interactions: Query<(&Interaction, &MenuItem), Changed<Interaction>>,
+    mut last_stick: Local<Option<MenuInput>>,
+    mut inputs: EventWriter<MenuInput>,
+) {
+    // Пока ждем новую клавишу, нажатия не управляют меню
+    if menu.stack.is_empty() || menu.rebinding.is_some() {
+        return;
+    }
+
+    let keys = [
+        ([KeyCode::ArrowUp, KeyCode::KeyW], MenuInput::Up),
+        ([KeyCode::ArrowDown, KeyCode::KeyS], MenuInput::Down),
+        ([KeyCode::ArrowLeft, KeyCode::KeyA], MenuInput::Left),
+        ([KeyCode::ArrowRight, KeyCode::KeyD], MenuInput::Right),
+        ([KeyCode::Enter, KeyCode::Space], MenuInput::Confirm),
+        ([KeyCode::Escape, KeyCode::Backspace], MenuInput::Back),
+    ];
+    for (codes, input) in keys {
+        if keyboard.any_just_pressed(codes) {
+            inputs.send(input);
+        }
+    }
+
+    let buttons = [
+        (GamepadButton::DPadUp, MenuInput::Up),
+        (GamepadButton::DPadDown, MenuInput::Down),
+        (GamepadButton::DPadLeft, MenuInput::Left),
+        (GamepadButton::DPadRight, MenuInput::Right),
+        (GamepadButton::South, MenuInput::Confirm),
+        (GamepadButton::East, MenuInput::Back),
+        (GamepadButton::Start, MenuInput::Back),
+    ];
+    for gamepad in gamepads.iter() {
+        for (button, input) in buttons {
+            if gamepad.just_pressed(button) {
+                inputs.send(input);
+            }
+        }
+    }
+
+    // Стик срабатывает один раз при отклонении, повтор - только после возврата в центр
+    let stick = gamepads.iter().map(Gamepad::left_stick).max_by(|a, b| a.length().total_cmp(&b.length()));
+    let direction = stick.and_then(|stick| {
+        let deadzone = menu.stick_deadzone;
+        if stick.y > deadzone {
+            Some(MenuInput::Up)
+        } else if stick.y < -deadzone {
+            Some(MenuInput::Down)
+        } else if stick.x > deadzone {
+            Some(MenuInput::Right)
+        } else if stick.x < -deadzone {
+            Some(MenuInput::Left)
+        } else {
+            None
+        }
+    });
+    if direction != *last_stick {
+        if let Some(input) = direction {
+            inputs.send(input);
+        }
+        *last_stick = direction;
+    }
+
+    for (interaction, item) in interactions.iter() {
+        match interaction {
+            Interaction::Hovered => {
+                inputs.send(MenuInput::Hover(item.0));
+            }
+            Interaction::Pressed => {
+                inputs.send(MenuInput::Click(item.0));
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+/// Первая нажатая клавиша или кнопка мыши достается ожидающему действию, Esc отменяет
+fn capture_binding(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut menu: ResMut<Menu>,
+    mut bindings: ResMut<KeyBindings>,
+) {
+    let Some(control) = menu.rebinding else {
+        return;
+    };
+    if keyboard.just_pressed(KeyCode::Escape) {
+        menu.rebinding = None;
+        return;
+    }
+    let pressed = keyboard.get_just_pressed().next().map(|key| Binding::Key(*key))
+        .or_else(|| mouse.get_just_pressed().next().map(|button| Binding::Mouse(*button)));
+    if let Some(binding) = pressed {
+        bindings.set(control, binding);
+        menu.rebinding = None;
+    }
+}
+
+fn apply_menu_input(
+    mut inputs: EventReader<MenuInput>,
+    mut menu: ResMut<Menu>,
+    mut settings: Settings,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut restarts: EventWriter<RestartLevel>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    for input in inputs.read() {
+        let Some(&(page, selected)) = menu.stack.last() else {
+            return;
+        };
+        let items = page.items();
+
+        let action = match *input {
+            MenuInput::Up => {
+                select(&mut menu, cycle(selected, -1, items.len()));
+                continue;
+            }
+            MenuInput::Down => {
+                select(&mut menu, cycle(selected, 1, items.len()));
+                continue;
+            }
+            MenuInput::Hover(index) => {
+                // Без лишней записи, чтобы меню не пересобиралось каждый кадр
+                if index != selected {
+                    select(&mut menu, index);
+                }
+                continue;
+            }
+            MenuInput::Left | MenuInput::Right => {
+                if let MenuAction::Setting(setting) = items[selected] {
+                    let step = if *input == MenuInput::Left { -1 } else { 1 };
+                    settings.adjust(setting, step, &mut menu);
+                }
+                continue;
+            }
+            MenuInput::Click(index) => {
+                select(&mut menu, index);
+                items[index]
+            }
+            MenuInput::Confirm => items[selected],
+            MenuInput::Back => MenuAction::Back,
+        };
+
+        match action {
+            MenuAction::Play | MenuAction::Resume => next_state.set(GameState::Playing),
+            MenuAction::Restart => {
+                restarts.send(RestartLevel);
+            }
+            MenuAction::Open(page) => menu.stack.push((page, 0)),
+            MenuAction::Back => {
+                if menu.stack.len() > 1 {
+                    menu.stack.pop();
+                } else if page == MenuPage::Pause {
+                    next_state.set(GameState::Playing);
+                }
+            }
+            MenuAction::QuitToMenu => next_state.set(GameState::MainMenu),
+            MenuAction::Quit => {
+                app_exit.send(AppExit::Success);
+            }
+            MenuAction::Setting(Setting::Binding(control)) => menu.rebinding = Some(control),
+            MenuAction::Setting(setting) => settings.adjust(setting, 1, &mut menu),
+        }
+    }
+}
+
+/// Страница меню пересоздается целиком при любом изменении: их немного и они маленькие
+fn rebuild_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    menu: Res<Menu>,
+    settings: Settings,
+    roots: Query<Entity, With<MenuRoot>>,
+) {
+    for root in roots.iter() {
+        commands.entity(root).despawn_recursive();
+    }
+    let Some(&(page, selected)) = menu.stack.last() else {
+        return;
+    };
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    // В главном меню уровня нет - фон непрозрачный, в паузе сквозь него виден замерший уровень
+    let background = if menu.stack[0].0 == MenuPage::Main { 0.95 } else { 0.6 };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, background)),
+            GlobalZIndex(10),
+            MenuRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(page.title()),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(24.0)),
+                    ..default()
+                },
+            ));
+
+            if page == MenuPage::Controls {
+                for (action, keys) in CONTROLS {
+                    parent.spawn((
+                        Text::new(format!("{}:  {}", action, keys)),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 20.0,
+                            ..default()
+                        },
+                        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    ));
+                }
+            }
+
+            for (index, action) in page.items().into_iter().enumerate() {
+                let label = match action {
+                    MenuAction::Setting(setting) => format!("{}:  < {} >", setting.label(), settings.value(setting, &menu)),
+                    _ => action.label().to_string(),
+                };
+                let color = if index == selected {
+                    Color::srgba(0.8, 0.2, 0.2, 0.9)
+                } else {
+                    Color::srgba(0.2, 0.2, 0.2, 0.8)
+                };
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(360.0),
+                            padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                        MenuItem(index),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(label),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: 26.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+
+            parent.spawn((
+                Text::new("Arrows / D-pad - select    Enter / A - confirm    Esc / B - back"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                Node {
+                    margin: UiRect::top(Val::Px(24.0)),
+                    ..default()
+                },
+            ));
+        });
+}
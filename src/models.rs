@@ -0,0 +1,228 @@
+// src/models.rs - Импорт glTF-сцен для уровней и моделей персонажей.
+// Для сцен уровня строятся коллайдеры из мешей: по умолчанию как задано в файле уровня,
+// для отдельных узлов - по суффиксу имени (-col, -convcol, -colonly) или по свойствам
+// узла (glTF extras), например {"collider": "convex", "ground": true}.
+// Узлы со свойством "marker" превращаются в точки спавна, предметы и зоны смерти (форма зоны - меш узла):
+// {"marker": "spawn_point", "group": "north"}, {"marker": "pickup", "item": "ammo", "respawn_time": 15},
+// {"marker": "kill_volume"}
+use bevy::gltf::GltfExtras;
+use bevy::prelude::*;
+use bevy::scene::SceneInstanceReady;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::navigation::NavMesh;
+use crate::pickups::PickupSpawner;
+use crate::state::InGame;
+use crate::world::{EnemySpawnPoint, Ground, KillVolume};
+
+pub struct ModelsPlugin;
+impl Plugin for ModelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(build_model_physics);
+    }
+}
+
+// Предмет из маркера без "respawn_time" появляется снова через столько секунд
+const DEFAULT_RESPAWN_TIME: f32 = 20.0;
+
+/// Какой коллайдер строить из меша
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ModelCollider {
+    /// Точная сетка треугольников - для статичной геометрии
+    #[default]
+    #[serde(alias = "trimesh")]
+    Trimesh,
+    /// Выпуклая оболочка - для реквизита
+    #[serde(alias = "convex")]
+    ConvexHull,
+    #[serde(alias = "none")]
+    None,
+}
+
+/// Сцена модели персонажа - дочерняя сущность врага
+#[derive(Component)]
+pub struct CharacterModelScene;
+
+/// Модель персонажа вместо простой фигуры. Коллайдер остается от фигуры
+#[derive(Deserialize, Clone)]
+pub struct CharacterModel {
+    /// Путь к .gltf/.glb в assets; берется первая сцена файла
+    pub path: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Смещение модели относительно центра коллайдера
+    #[serde(default)]
+    pub offset: [f32; 3],
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl CharacterModel {
+    /// Сцена модели - дочерняя сущность персонажа
+    pub fn bundle(&self, asset_server: &AssetServer) -> impl Bundle {
+        (
+            SceneRoot(asset_server.load(GltfAssetLabel::Scene(0).from_asset(self.path.clone()))),
+            Transform::from_translation(Vec3::from(self.offset)).with_scale(Vec3::splat(self.scale)),
+            // Сам персонаж скрыт (его меш нужен для обломков и вспышек), модель видна всегда
+            Visibility::Visible,
+            CharacterModelScene,
+            Name::new("Character Model"),
+        )
+    }
+}
+
+/// Сцена уровня: после появления в мире ее меши получают коллайдеры
+#[derive(Component)]
+pub struct LevelModel {
+    /// Коллайдер для узлов без своих настроек
+    pub collider: ModelCollider,
+}
+
+/// Свойства узла из glTF extras; незнакомые поля игнорируются
+#[derive(Deserialize, Default)]
+struct NodeProperties {
+    collider: Option<ModelCollider>,
+    #[serde(default)]
+    ground: bool,
+    marker: Option<NodeMarker>,
+    group: Option<String>,
+    item: Option<String>,
+    respawn_time: Option<f32>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum NodeMarker {
+    SpawnPoint,
+    Pickup,
+    KillVolume,
+}
+
+/// Настройки, которые узел передает своим мешам и дочерним узлам
+#[derive(Clone, Copy)]
+struct NodeSettings {
+    collider: ModelCollider,
+    ground: bool,
+    kill_volume: bool,
+}
+
+fn build_model_physics(
+    trigger: Trigger<SceneInstanceReady>,
+    mut commands: Commands,
+    models: Query<&LevelModel>,
+    children: Query<&Children>,
+    nodes: Query<(&Transform, Option<&Name>, Option<&GltfExtras>, Option<&Mesh3d>)>,
+    meshes: Res<Assets<Mesh>>,
+    mut navmesh: ResMut<NavMesh>,
+) {
+    let root = trigger.entity();
+    let Ok(model) = models.get(root) else {
+        return;
+    };
+    let Ok((root_transform, ..)) = nodes.get(root) else {
+        return;
+    };
+
+    // GlobalTransform посчитается только в конце кадра - собираем мировые координаты сами
+    let mut stack = vec![(
+        root,
+        GlobalTransform::from(*root_transform),
+        NodeSettings { collider: model.collider, ground: false, kill_volume: false },
+    )];
+    let mut colliders = 0;
+    while let Some((entity, world, inherited)) = stack.pop() {
+        let Ok((_, name, extras, mesh)) = nodes.get(entity) else {
+            continue;
+        };
+        let mut settings = inherited;
+
+        if let Some(name) = name {
+            let name = name.as_str();
+            if name.ends_with("-colonly") {
+                // Невидимая геометрия только для столкновений
+                settings.collider = ModelCollider::Trimesh;
+                commands.entity(entity).insert(Visibility::Hidden);
+            } else if name.ends_with("-convcol") {
+                settings.collider = ModelCollider::ConvexHull;
+            } else if name.ends_with("-col") {
+                settings.collider = ModelCollider::Trimesh;
+            }
+        }
+
+        if let Some(extras) = extras {
+            let properties = serde_json::from_str::<NodeProperties>(&extras.value).unwrap_or_else(|error| {
+                warn!("Bad glTF extras on {:?}: {}", name, error);
+                NodeProperties::default()
+            });
+            if let Some(collider) = properties.collider {
+                settings.collider = collider;
+            }
+            settings.ground |= properties.ground;
+            let position = world.translation();
+            match properties.marker {
+                Some(NodeMarker::SpawnPoint) => {
+                    commands.spawn((
+                        Transform::from_translation(position),
+                        EnemySpawnPoint { group: properties.group.unwrap_or_default() },
+                        Name::new("Enemy Spawn Point"),
+                        StateScoped(InGame),
+                    ));
+                }
+                Some(NodeMarker::Pickup) => match properties.item {
+                    Some(item) => {
+                        commands.spawn((
+                            Transform::from_translation(position),
+                            PickupSpawner::new(&item, properties.respawn_time.unwrap_or(DEFAULT_RESPAWN_TIME)),
+                            Name::new("Pickup Point"),
+                            StateScoped(InGame),
+                        ));
+                    }
+                    None => warn!("Pickup marker {:?} has no \"item\"", name),
+                },
+                Some(NodeMarker::KillVolume) => {
+                    settings.kill_volume = true;
+                    commands.entity(entity).insert(Visibility::Hidden);
+                }
+                None => {}
+            }
+        }
+
+        if let Some(mesh) = mesh.and_then(|mesh| meshes.get(&mesh.0)) {
+            let shape = match settings.collider {
+                _ if settings.kill_volume => Some(ComputedColliderShape::ConvexHull),
+                ModelCollider::Trimesh => Some(ComputedColliderShape::TriMesh(TriMeshFlags::MERGE_DUPLICATE_VERTICES)),
+                ModelCollider::ConvexHull => Some(ComputedColliderShape::ConvexHull),
+                ModelCollider::None => None,
+            };
+            match shape.map(|shape| Collider::from_bevy_mesh(mesh, &shape)) {
+                Some(Some(collider)) => {
+                    let mut entity = commands.entity(entity);
+                    if settings.kill_volume {
+                        entity.insert((collider, Sensor, ActiveCollisionTypes::all(), KillVolume));
+                    } else {
+                        entity.insert((RigidBody::Fixed, collider));
+                    }
+                    if settings.ground {
+                        entity.insert(Ground);
+                    }
+                    colliders += 1;
+                }
+                Some(None) => warn!("Could not build a collider for {:?}", name),
+                None => {}
+            }
+        }
+
+        for &child in children.get(entity).into_iter().flatten() {
+            if let Ok((transform, ..)) = nodes.get(child) {
+                stack.push((child, world.mul_transform(*transform), settings));
+            }
+        }
+    }
+    // Сцена догружается позже уровня - сетка уже запечена без ее стен
+    if colliders > 0 {
+        navmesh.dirty = true;
+    }
+    info!("Level model ready: {} colliders", colliders);
+}
@@ -0,0 +1,395 @@
+// src/movers.rs - Подвижная геометрия: лифты, двери, вращающиеся платформы.
+// Кинематическое тело идет по точкам пути с плавным разгоном; игрока, стоящего сверху,
+// везет вместе с собой. Запускается сразу, зоной-триггером или кнопкой (клавиша E)
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::enemies::{Health, Hit};
+use crate::player::{apply_acceleration_cpma, move_kinematic_player_by_velocity, Player};
+use crate::state::GameState;
+
+pub struct MoversPlugin;
+impl Plugin for MoversPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ActivateMover>()
+           .add_systems(Update, (
+               use_buttons,
+               activate_by_triggers,
+               activate_movers,
+           ).chain().run_if(in_state(GameState::Playing)))
+           .add_systems(FixedUpdate, (
+               move_movers.after(apply_acceleration_cpma),
+               // Поверх собственного движения игрока за этот шаг
+               carry_riders.after(move_kinematic_player_by_velocity),
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// На каком расстоянии от кнопки работает клавиша E
+const USE_DISTANCE: f32 = 2.0;
+// Насколько ниже центра игрока ищем опору под ногами (низ капсулы - 0.8)
+const RIDER_PROBE_DISTANCE: f32 = 1.0;
+
+/// Запустить подвижное тело; повторный запуск во время движения ничего не делает
+#[derive(Event)]
+pub struct ActivateMover(pub Entity);
+
+/// Точка пути относительно исходного положения тела. Первая точка - само исходное положение
+#[derive(Deserialize, Clone)]
+pub struct MoverWaypoint {
+    #[serde(default)]
+    pub offset: [f32; 3],
+    /// Поворот в градусах вокруг осей X, Y, Z
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    /// Время пути до этой точки с предыдущей (для первой - с последней при `Loop`)
+    #[serde(default = "default_duration")]
+    pub duration: f32,
+    /// Пауза в этой точке
+    #[serde(default)]
+    pub wait: f32,
+}
+
+fn default_duration() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum Easing {
+    Linear,
+    #[default]
+    Smooth,
+    EaseIn,
+    EaseOut,
+}
+
+impl Easing {
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smooth => t * t * (3.0 - 2.0 * t),
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MoverMode {
+    /// Туда и обратно. Запущенный триггером или кнопкой возвращается и ждет следующего запуска
+    #[default]
+    PingPong,
+    /// По кругу: с последней точки на первую
+    Loop,
+    /// До последней точки и остановка
+    Once,
+}
+
+/// Что делать, если на пути кто-то стоит
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum BlockedBehavior {
+    /// Повернуть обратно, как дверь лифта
+    #[default]
+    Reverse,
+    /// Стоять и давить: урон в секунду, пока препятствие не исчезнет
+    Crush { damage: f32 },
+}
+
+#[derive(Component)]
+pub struct Mover {
+    /// Номер тела в описании уровня - по нему восстанавливается сохранение
+    pub index: usize,
+    origin: Transform,
+    waypoints: Vec<MoverWaypoint>,
+    easing: Easing,
+    mode: MoverMode,
+    blocked: BlockedBehavior,
+    /// Чуть уменьшенная форма тела: проверка, не зажат ли кто-то в новом положении
+    probe: Collider,
+    /// Движется без запуска (иначе - ждет `ActivateMover`)
+    automatic: bool,
+    running: bool,
+    /// Текущий отрезок пути: из какой точки в какую
+    from: usize,
+    to: usize,
+    progress: f32,
+    wait: f32,
+    /// Положение до последнего шага - по нему везем стоящих сверху
+    previous: Transform,
+}
+
+/// Положение тела на пути для сохранения игры
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MoverState {
+    pub index: usize,
+    running: bool,
+    from: usize,
+    to: usize,
+    progress: f32,
+    wait: f32,
+}
+
+impl Mover {
+    pub fn new(
+        index: usize,
+        origin: Transform,
+        waypoints: Vec<MoverWaypoint>,
+        easing: Easing,
+        mode: MoverMode,
+        blocked: BlockedBehavior,
+        probe: Collider,
+        automatic: bool,
+    ) -> Self {
+        let wait = waypoints.first().map_or(0.0, |waypoint| waypoint.wait);
+        Self {
+            index,
+            origin,
+            waypoints,
+            easing,
+            mode,
+            blocked,
+            probe,
+            automatic,
+            running: automatic,
+            from: 0,
+            to: 1,
+            progress: 0.0,
+            wait,
+            previous: origin,
+        }
+    }
+
+    pub fn state(&self) -> MoverState {
+        MoverState {
+            index: self.index,
+            running: self.running,
+            from: self.from,
+            to: self.to,
+            progress: self.progress,
+            wait: self.wait,
+        }
+    }
+
+    /// Возвращает тело на сохраненное место пути. Сохранение от другой версии уровня пропускается
+    pub fn restore(&mut self, state: &MoverState, transform: &mut Transform) {
+        let count = self.waypoints.len();
+        if state.from >= count || state.to >= count {
+            return;
+        }
+        self.running = state.running;
+        self.from = state.from;
+        self.to = state.to;
+        self.progress = state.progress;
+        self.wait = state.wait;
+        *transform = self.pose_at(self.progress);
+        self.previous = *transform;
+    }
+
+    fn pose(&self, index: usize) -> Transform {
+        let waypoint = &self.waypoints[index];
+        let [x, y, z] = waypoint.rotation;
+        let rotation = Quat::from_euler(EulerRot::XYZ, x.to_radians(), y.to_radians(), z.to_radians());
+        Transform {
+            translation: self.origin.translation + Vec3::from(waypoint.offset),
+            rotation: self.origin.rotation * rotation,
+            scale: self.origin.scale,
+        }
+    }
+
+    fn pose_at(&self, progress: f32) -> Transform {
+        let (from, to) = (self.pose(self.from), self.pose(self.to));
+        let t = self.easing.apply(progress.clamp(0.0, 1.0));
+        Transform {
+            translation: from.translation.lerp(to.translation, t),
+            rotation: from.rotation.slerp(to.rotation, t),
+            scale: from.scale,
+        }
+    }
+
+    /// Время текущего отрезка: отрезок принадлежит точке с большим номером
+    fn leg_duration(&self) -> f32 {
+        let owner = if self.mode == MoverMode::Loop { self.to } else { self.from.max(self.to) };
+        self.waypoints[owner].duration.max(0.01)
+    }
+
+    /// Пришли в точку `to` - выбираем следующий отрезок
+    fn arrive(&mut self) {
+        let last = self.waypoints.len() - 1;
+        let arrived = self.to;
+        self.wait = self.waypoints[arrived].wait;
+        self.progress = 0.0;
+        let forward = self.to > self.from || (self.mode == MoverMode::Loop);
+        let next = match self.mode {
+            MoverMode::Loop => (arrived + 1) % self.waypoints.len(),
+            MoverMode::Once if arrived == last => {
+                self.running = false;
+                arrived
+            }
+            MoverMode::Once => arrived + 1,
+            MoverMode::PingPong if arrived == last => arrived - 1,
+            MoverMode::PingPong if arrived == 0 => {
+                // Вернулись в исходное положение: запущенное вручную ждет следующего запуска
+                if !self.automatic {
+                    self.running = false;
+                }
+                1
+            }
+            MoverMode::PingPong if forward => arrived + 1,
+            MoverMode::PingPong => arrived - 1,
+        };
+        self.from = arrived;
+        self.to = next;
+    }
+
+    fn reverse(&mut self) {
+        std::mem::swap(&mut self.from, &mut self.to);
+        self.progress = 1.0 - self.progress;
+    }
+}
+
+/// Зона, которая запускает тело, когда в нее входит игрок
+#[derive(Component)]
+pub struct MoverTrigger {
+    pub mover: Entity,
+}
+
+/// Кнопка: запускает тело по клавише E рядом с ней
+#[derive(Component)]
+pub struct MoverButton {
+    pub mover: Entity,
+}
+
+fn use_buttons(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    player_query: Query<&Transform, With<Player>>,
+    buttons: Query<(&Transform, &MoverButton)>,
+    mut activations: EventWriter<ActivateMover>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    // Ближайшая кнопка в пределах досягаемости
+    let nearest = buttons
+        .iter()
+        .map(|(transform, button)| (transform.translation.distance(player.translation), button.mover))
+        .filter(|(distance, _)| *distance <= USE_DISTANCE)
+        .min_by(|a, b| a.0.total_cmp(&b.0));
+    if let Some((_, mover)) = nearest {
+        activations.send(ActivateMover(mover));
+    }
+}
+
+fn activate_by_triggers(
+    rapier_context: ReadRapierContext,
+    player_query: Query<Entity, With<Player>>,
+    triggers: Query<(Entity, &MoverTrigger)>,
+    mut activations: EventWriter<ActivateMover>,
+) {
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+    let context = rapier_context.single();
+    for (entity, trigger) in triggers.iter() {
+        if context.intersection_pair(entity, player) == Some(true) {
+            activations.send(ActivateMover(trigger.mover));
+        }
+    }
+}
+
+fn activate_movers(mut activations: EventReader<ActivateMover>, mut movers: Query<&mut Mover>) {
+    for ActivateMover(entity) in activations.read() {
+        let Ok(mut mover) = movers.get_mut(*entity) else {
+            continue;
+        };
+        if !mover.running && mover.waypoints.len() > 1 {
+            mover.running = true;
+        }
+    }
+}
+
+fn move_movers(
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    mut movers: Query<(Entity, &mut Mover, &mut Transform)>,
+    mut victims: Query<&mut Health>,
+) {
+    let context = rapier_context.single();
+    let dt = time.delta_secs();
+    for (entity, mut mover, mut transform) in movers.iter_mut() {
+        mover.previous = *transform;
+        if !mover.running || mover.waypoints.len() < 2 {
+            continue;
+        }
+        if mover.wait > 0.0 {
+            mover.wait -= dt;
+            continue;
+        }
+
+        let progress = mover.progress + dt / mover.leg_duration();
+        let pose = mover.pose_at(progress);
+
+        // Кто окажется внутри тела в новом положении (стоящие сверху не в счет - форма уменьшена)
+        let mut blockers = Vec::new();
+        context.intersections_with_shape(
+            pose.translation,
+            pose.rotation,
+            &mover.probe,
+            QueryFilter::new().exclude_sensors().exclude_collider(entity),
+            |other| {
+                if victims.contains(other) {
+                    blockers.push(other);
+                }
+                true
+            },
+        );
+        if !blockers.is_empty() {
+            match mover.blocked {
+                BlockedBehavior::Reverse => mover.reverse(),
+                BlockedBehavior::Crush { damage } => {
+                    for blocker in blockers {
+                        if let Ok(mut health) = victims.get_mut(blocker) {
+                            health.take_damage(damage * dt, Hit { source: None, impulse: Vec3::ZERO });
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+
+        *transform = pose;
+        mover.progress = progress;
+        if progress >= 1.0 {
+            mover.arrive();
+        }
+    }
+}
+
+/// Игрок на движущемся теле едет вместе с ним: к его перемещению за шаг
+/// добавляется смещение точки опоры
+fn carry_riders(
+    rapier_context: ReadRapierContext,
+    movers: Query<(&Mover, &Transform)>,
+    mut player_query: Query<(Entity, &Transform, &mut KinematicCharacterController), With<Player>>,
+) {
+    let Ok((player, transform, mut controller)) = player_query.get_single_mut() else {
+        return;
+    };
+    let context = rapier_context.single();
+    let filter = QueryFilter::new().exclude_sensors().exclude_collider(player);
+    let Some((ground, _)) = context.cast_ray(transform.translation, Vec3::NEG_Y, RIDER_PROBE_DISTANCE, true, filter) else {
+        return;
+    };
+    let Ok((mover, current)) = movers.get(ground) else {
+        return;
+    };
+    let previous = mover.previous;
+    let local = previous.rotation.inverse() * (transform.translation - previous.translation);
+    let carried = current.translation + current.rotation * local;
+    let delta = carried - transform.translation;
+    if delta != Vec3::ZERO {
+        controller.translation = Some(controller.translation.unwrap_or(Vec3::ZERO) + delta);
+    }
+}
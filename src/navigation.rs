@@ -0,0 +1,566 @@
+// src/navigation.rs - Навигационная сетка, поиск пути A* и следование по пути для врагов
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::rapier::math::Isometry;
+use crate::world::Ground;
+use crate::state::{GameState, InGame};
+
+pub struct NavigationPlugin;
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavMesh>()
+           .init_resource::<NavDebug>()
+           .add_systems(OnEnter(InGame), invalidate_navmesh)
+           .add_systems(Update, (
+               bake_navmesh,
+               update_nav_paths,
+           ).chain().run_if(in_state(GameState::Playing)))
+           .add_systems(Update, (
+               toggle_nav_debug,
+               draw_navigation,
+           ));
+    }
+}
+
+// Высота агента: препятствия выше пола на эту величину не блокируют проход
+const AGENT_HEIGHT: f32 = 2.0;
+// Препятствия ниже этой высоты агент перешагивает
+const STEP_HEIGHT: f32 = 0.3;
+
+// Стоимость перехода между клетками (целые числа, чтобы не сравнивать f32 в куче)
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+
+/// Навигационная сетка, запеченная из статичных коллайдеров Rapier.
+/// Пол (`Ground`) задает проходимую область, остальные `RigidBody::Fixed`
+/// коллайдеры вырезают из нее непроходимые клетки с учетом радиуса агента.
+#[derive(Resource)]
+pub struct NavMesh {
+    pub cell_size: f32,
+    pub agent_radius: f32,
+    pub origin: Vec3,    // Угол сетки с минимальными X/Z, Y - высота пола
+    pub width: usize,    // Количество клеток по X
+    pub depth: usize,    // Количество клеток по Z
+    walkable: Vec<bool>,
+    /// Выставляется, когда статичная геометрия изменилась и сетку нужно перезапечь
+    pub dirty: bool,
+}
+
+impl Default for NavMesh {
+    fn default() -> Self {
+        Self {
+            cell_size: 0.5,
+            agent_radius: 0.6, // Половина куба врага плюс небольшой запас
+            origin: Vec3::ZERO,
+            width: 0,
+            depth: 0,
+            walkable: Vec::new(),
+            dirty: true,
+        }
+    }
+}
+
+impl NavMesh {
+    pub fn is_baked(&self) -> bool {
+        !self.walkable.is_empty()
+    }
+
+    pub fn world_to_cell(&self, position: Vec3) -> Option<UVec2> {
+        let local = (position - self.origin) / self.cell_size;
+        if local.x < 0.0 || local.z < 0.0 {
+            return None;
+        }
+        let (x, z) = (local.x as usize, local.z as usize);
+        (x < self.width && z < self.depth).then(|| UVec2::new(x as u32, z as u32))
+    }
+
+    pub fn cell_center(&self, cell: UVec2) -> Vec3 {
+        self.origin + Vec3::new(
+            (cell.x as f32 + 0.5) * self.cell_size,
+            0.0,
+            (cell.y as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    pub fn is_walkable(&self, cell: UVec2) -> bool {
+        let (x, z) = (cell.x as usize, cell.y as usize);
+        x < self.width && z < self.depth && self.walkable[z * self.width + x]
+    }
+
+    fn neighbors(&self, cell: UVec2) -> impl Iterator<Item = (UVec2, u32)> + '_ {
+        const OFFSETS: [(i32, i32); 8] = [
+            (1, 0), (-1, 0), (0, 1), (0, -1),
+            (1, 1), (1, -1), (-1, 1), (-1, -1),
+        ];
+        OFFSETS.iter().filter_map(move |&(dx, dz)| {
+            let next = cell.as_ivec2() + IVec2::new(dx, dz);
+            if next.x < 0 || next.y < 0 {
+                return None;
+            }
+            let next = next.as_uvec2();
+            if !self.is_walkable(next) {
+                return None;
+            }
+            if dx != 0 && dz != 0 {
+                // Не срезаем углы препятствий по диагонали
+                let side_x = UVec2::new(next.x, cell.y);
+                let side_z = UVec2::new(cell.x, next.y);
+                if !self.is_walkable(side_x) || !self.is_walkable(side_z) {
+                    return None;
+                }
+                return Some((next, DIAGONAL_COST));
+            }
+            Some((next, STRAIGHT_COST))
+        })
+    }
+
+    /// Ближайшая проходимая клетка - цель или старт могут оказаться внутри препятствия
+    fn nearest_walkable(&self, cell: UVec2) -> Option<UVec2> {
+        if self.is_walkable(cell) {
+            return Some(cell);
+        }
+        let max_radius = self.width.max(self.depth) as i32;
+        for radius in 1..max_radius {
+            let mut best: Option<(UVec2, i32)> = None;
+            for dz in -radius..=radius {
+                for dx in -radius..=radius {
+                    if dx.abs() != radius && dz.abs() != radius {
+                        continue;
+                    }
+                    let candidate = cell.as_ivec2() + IVec2::new(dx, dz);
+                    if candidate.x < 0 || candidate.y < 0 {
+                        continue;
+                    }
+                    let candidate = candidate.as_uvec2();
+                    let distance = dx * dx + dz * dz;
+                    if self.is_walkable(candidate) && best.is_none_or(|(_, d)| distance < d) {
+                        best = Some((candidate, distance));
+                    }
+                }
+            }
+            if let Some((found, _)) = best {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Прямая видимость по сетке: все клетки под отрезком (с учетом ширины агента) проходимы
+    pub fn has_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+        let delta = Vec3::new(to.x - from.x, 0.0, to.z - from.z);
+        let length = delta.length();
+        if length < f32::EPSILON {
+            return self.world_to_cell(from).is_some_and(|cell| self.is_walkable(cell));
+        }
+        let step = self.cell_size * 0.25;
+        let steps = (length / step).ceil() as usize;
+        let side = Vec3::new(-delta.z, 0.0, delta.x) / length * (self.cell_size * 0.45);
+        (0..=steps).all(|i| {
+            let point = from + delta * (i as f32 / steps as f32);
+            [point, point + side, point - side].iter().all(|&sample| {
+                self.world_to_cell(sample).is_some_and(|cell| self.is_walkable(cell))
+            })
+        })
+    }
+
+    /// Поиск пути A* с последующим "натягиванием нити" (string pulling).
+    /// Возвращает точки пути без стартовой позиции, последняя точка - цель.
+    pub fn find_path(&self, start: Vec3, goal: Vec3) -> Option<Vec<Vec3>> {
+        if !self.is_baked() {
+            return None;
+        }
+        let start_cell = self.nearest_walkable(self.world_to_cell(start)?)?;
+        let goal_cell = self.nearest_walkable(self.world_to_cell(goal)?)?;
+
+        let index = |cell: UVec2| cell.y as usize * self.width + cell.x as usize;
+        let heuristic = |cell: UVec2| {
+            let dx = cell.x.abs_diff(goal_cell.x);
+            let dz = cell.y.abs_diff(goal_cell.y);
+            STRAIGHT_COST * dx.max(dz) + (DIAGONAL_COST - STRAIGHT_COST) * dx.min(dz)
+        };
+
+        let cell_count = self.width * self.depth;
+        let mut cost_so_far = vec![u32::MAX; cell_count];
+        let mut came_from: Vec<Option<UVec2>> = vec![None; cell_count];
+        let mut open = BinaryHeap::new();
+
+        cost_so_far[index(start_cell)] = 0;
+        open.push(OpenNode { cell: start_cell, priority: heuristic(start_cell) });
+
+        while let Some(OpenNode { cell, .. }) = open.pop() {
+            if cell == goal_cell {
+                break;
+            }
+            let current_cost = cost_so_far[index(cell)];
+            for (next, step_cost) in self.neighbors(cell) {
+                let new_cost = current_cost + step_cost;
+                if new_cost < cost_so_far[index(next)] {
+                    cost_so_far[index(next)] = new_cost;
+                    came_from[index(next)] = Some(cell);
+                    open.push(OpenNode { cell: next, priority: new_cost + heuristic(next) });
+                }
+            }
+        }
+
+        if cost_so_far[index(goal_cell)] == u32::MAX {
+            return None;
+        }
+
+        // Восстанавливаем цепочку клеток от цели к старту
+        let mut cells = vec![goal_cell];
+        let mut current = goal_cell;
+        while let Some(previous) = came_from[index(current)] {
+            cells.push(previous);
+            current = previous;
+        }
+        cells.reverse();
+
+        let mut points: Vec<Vec3> = cells.iter().map(|&cell| self.cell_center(cell)).collect();
+        // Точная цель вместо центра клетки, если она проходима
+        if let (Some(last), true) = (points.last_mut(), self.world_to_cell(goal) == Some(goal_cell)) {
+            *last = Vec3::new(goal.x, self.origin.y, goal.z);
+        }
+        let start_point = Vec3::new(start.x, self.origin.y, start.z);
+        Some(self.string_pull(start_point, &points))
+    }
+
+    /// Выкидываем промежуточные точки, между которыми есть прямая видимость
+    fn string_pull(&self, start: Vec3, points: &[Vec3]) -> Vec<Vec3> {
+        let mut result = Vec::new();
+        let mut anchor = start;
+        let mut i = 0;
+        while i < points.len() {
+            let furthest = (i + 1..points.len())
+                .rev()
+                .find(|&j| self.has_line_of_sight(anchor, points[j]))
+                .unwrap_or(i);
+            anchor = points[furthest];
+            result.push(anchor);
+            i = furthest + 1;
+        }
+        result
+    }
+}
+
+#[derive(PartialEq, Eq)]
+struct OpenNode {
+    cell: UVec2,
+    priority: u32,
+}
+
+impl Ord for OpenNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap - max-куча, поэтому инвертируем сравнение
+        other.priority.cmp(&self.priority)
+            .then_with(|| (self.cell.x, self.cell.y).cmp(&(other.cell.x, other.cell.y)))
+    }
+}
+
+impl PartialOrd for OpenNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Агент, который ходит по навигационной сетке к цели `goal`
+#[derive(Component)]
+pub struct NavAgent {
+    pub goal: Option<Vec3>,
+    pub path: Vec<Vec3>,
+    pub repath_interval: f32,    // Как часто перестраивать путь (сек)
+    pub repath_distance: f32,    // Перестраиваем раньше, если цель сместилась дальше
+    pub waypoint_radius: f32,    // Радиус, в котором точка пути считается достигнутой
+    repath_timer: f32,
+    path_goal: Option<Vec3>,
+}
+
+impl Default for NavAgent {
+    fn default() -> Self {
+        Self {
+            goal: None,
+            path: Vec::new(),
+            repath_interval: 0.5,
+            repath_distance: 1.5,
+            waypoint_radius: 0.4,
+            repath_timer: 0.0,
+            path_goal: None,
+        }
+    }
+}
+
+impl NavAgent {
+    /// Направление движения по горизонтали: к следующей точке пути,
+    /// а если пути нет - напрямую к цели
+    pub fn steering_direction(&self, position: Vec3) -> Vec3 {
+        let Some(target) = self.path.first().copied().or(self.goal) else {
+            return Vec3::ZERO;
+        };
+        Vec3::new(target.x - position.x, 0.0, target.z - position.z).normalize_or_zero()
+    }
+}
+
+/// Новый уровень - новая геометрия
+fn invalidate_navmesh(mut navmesh: ResMut<NavMesh>) {
+    navmesh.dirty = true;
+}
+
+fn bake_navmesh(
+    mut navmesh: ResMut<NavMesh>,
+    colliders: Query<(&Collider, &GlobalTransform, &RigidBody, Has<Ground>)>,
+) {
+    if !navmesh.dirty {
+        return;
+    }
+
+    let aabbs: Vec<(Vec3, Vec3, bool)> = colliders
+        .iter()
+        .filter(|(_, _, body, _)| **body == RigidBody::Fixed)
+        .map(|(collider, transform, _, is_ground)| {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            let aabb = collider.raw.compute_aabb(&Isometry::from_parts(translation.into(), rotation.into()));
+            (aabb.mins.into(), aabb.maxs.into(), is_ground)
+        })
+        .collect();
+
+    // Проходимая область - объединение всех коллайдеров пола
+    let mut floor_min = Vec3::splat(f32::MAX);
+    let mut floor_max = Vec3::splat(f32::MIN);
+    for (min, max, _) in aabbs.iter().filter(|(_, _, is_ground)| *is_ground) {
+        floor_min = floor_min.min(*min);
+        floor_max = floor_max.max(*max);
+    }
+    if floor_min.x > floor_max.x {
+        // Пол еще не заспавнен - попробуем в следующем кадре
+        return;
+    }
+
+    let cell_size = navmesh.cell_size;
+    let radius = navmesh.agent_radius;
+    let floor_y = floor_max.y;
+    let width = ((floor_max.x - floor_min.x) / cell_size).floor() as usize;
+    let depth = ((floor_max.z - floor_min.z) / cell_size).floor() as usize;
+
+    navmesh.origin = Vec3::new(floor_min.x, floor_y, floor_min.z);
+    navmesh.width = width;
+    navmesh.depth = depth;
+    navmesh.walkable = vec![true; width * depth];
+
+    for z in 0..depth {
+        for x in 0..width {
+            let center = navmesh.cell_center(UVec2::new(x as u32, z as u32));
+            // Край арены тоже непроходим для агента с ненулевым радиусом
+            let near_edge = center.x - radius < floor_min.x
+                || center.x + radius > floor_max.x
+                || center.z - radius < floor_min.z
+                || center.z + radius > floor_max.z;
+            let blocked = near_edge || aabbs.iter().any(|(min, max, is_ground)| {
+                !is_ground
+                    && max.y > floor_y + STEP_HEIGHT
+                    && min.y < floor_y + AGENT_HEIGHT
+                    && center.x > min.x - radius && center.x < max.x + radius
+                    && center.z > min.z - radius && center.z < max.z + radius
+            });
+            navmesh.walkable[z * width + x] = !blocked;
+        }
+    }
+
+    navmesh.dirty = false;
+    info!("NavMesh baked: {}x{} cells", width, depth);
+}
+
+pub fn update_nav_paths(
+    time: Res<Time>,
+    navmesh: Res<NavMesh>,
+    mut agents: Query<(Entity, &Transform, &mut NavAgent)>,
+) {
+    for (entity, transform, mut agent) in agents.iter_mut() {
+        let position = transform.translation;
+        let Some(goal) = agent.goal else {
+            agent.path.clear();
+            agent.path_goal = None;
+            continue;
+        };
+
+        agent.repath_timer -= time.delta_secs();
+        let goal_moved = agent.path_goal
+            .is_none_or(|path_goal| path_goal.distance(goal) > agent.repath_distance);
+
+        if agent.repath_timer <= 0.0 || goal_moved {
+            // Разносим перестройку путей разных агентов по кадрам
+            let stagger = (entity.index() % 8) as f32 * 0.02;
+            agent.repath_timer = agent.repath_interval + stagger;
+            agent.path_goal = Some(goal);
+            agent.path = navmesh.find_path(position, goal).unwrap_or_default();
+        }
+
+        // Снимаем достигнутые точки пути
+        let waypoint_radius = agent.waypoint_radius;
+        while let Some(next) = agent.path.first() {
+            let horizontal = Vec2::new(next.x - position.x, next.z - position.z);
+            if horizontal.length() > waypoint_radius || agent.path.len() == 1 {
+                break;
+            }
+            agent.path.remove(0);
+        }
+    }
+}
+
+/// Отрисовка навигационной сетки и текущих путей (переключается на F3)
+#[derive(Resource, Default)]
+pub struct NavDebug {
+    pub enabled: bool,
+}
+
+fn toggle_nav_debug(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut debug: ResMut<NavDebug>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        debug.enabled = !debug.enabled;
+    }
+}
+
+fn draw_navigation(
+    debug: Res<NavDebug>,
+    navmesh: Res<NavMesh>,
+    agents: Query<(&Transform, &NavAgent)>,
+    mut gizmos: Gizmos,
+) {
+    if !debug.enabled || !navmesh.is_baked() {
+        return;
+    }
+
+    let lift = Vec3::Y * 0.02;
+    let flat = Quat::from_rotation_x(-std::f32::consts::FRAC_PI_2);
+    let cell_size = Vec2::splat(navmesh.cell_size * 0.9);
+    for z in 0..navmesh.depth {
+        for x in 0..navmesh.width {
+            let cell = UVec2::new(x as u32, z as u32);
+            if !navmesh.is_walkable(cell) {
+                gizmos.rect(
+                    Isometry3d::new(navmesh.cell_center(cell) + lift, flat),
+                    cell_size,
+                    Color::srgba(1.0, 0.2, 0.2, 0.6),
+                );
+            }
+        }
+    }
+
+    for (transform, agent) in agents.iter() {
+        if agent.path.is_empty() {
+            continue;
+        }
+        let start = Vec3::new(transform.translation.x, navmesh.origin.y, transform.translation.z);
+        gizmos.linestrip(
+            std::iter::once(start).chain(agent.path.iter().copied()).map(|point| point + lift * 5.0),
+            Color::srgb(0.2, 1.0, 0.3),
+        );
+        for point in &agent.path {
+            gizmos.sphere(Isometry3d::from_translation(*point + lift * 5.0), 0.1, Color::srgb(0.2, 1.0, 0.3));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Сетка с клетками 1x1 м от начала координат: '#' - препятствие, первая строка - z = 0
+    fn grid(rows: &[&str]) -> NavMesh {
+        let width = rows[0].len();
+        let walkable = rows.iter().flat_map(|row| row.chars().map(|cell| cell != '#')).collect();
+        NavMesh {
+            cell_size: 1.0,
+            origin: Vec3::ZERO,
+            width,
+            depth: rows.len(),
+            walkable,
+            dirty: false,
+            ..default()
+        }
+    }
+
+    fn assert_walkable_path(navmesh: &NavMesh, start: Vec3, path: &[Vec3]) {
+        let mut from = start;
+        for &point in path {
+            assert!(navmesh.has_line_of_sight(from, point), "segment {:?} -> {:?} crosses an obstacle", from, point);
+            from = point;
+        }
+    }
+
+    #[test]
+    fn unbaked_navmesh_has_no_path() {
+        assert!(NavMesh::default().find_path(Vec3::ZERO, Vec3::ONE).is_none());
+    }
+
+    #[test]
+    fn straight_path_is_pulled_to_goal() {
+        let navmesh = grid(&["........", "........", "........"]);
+        let goal = Vec3::new(7.5, 0.0, 2.5);
+        let path = navmesh.find_path(Vec3::new(0.5, 0.0, 0.5), goal).unwrap();
+        assert_eq!(path, vec![goal]);
+    }
+
+    #[test]
+    fn path_goes_around_wall_through_gap() {
+        let navmesh = grid(&[
+            "........",
+            "........",
+            "#######.",
+            "........",
+            "........",
+        ]);
+        let start = Vec3::new(0.5, 0.0, 0.5);
+        let goal = Vec3::new(0.5, 0.0, 4.5);
+        let path = navmesh.find_path(start, goal).unwrap();
+
+        assert_eq!(*path.last().unwrap(), goal);
+        assert!(path.iter().any(|point| point.x > 7.0), "path {:?} does not use the gap", path);
+        assert_walkable_path(&navmesh, start, &path);
+        // Натянутая нить: только угловые точки у прохода и цель
+        assert!(path.len() <= 3, "path {:?} was not pulled", path);
+    }
+
+    #[test]
+    fn path_does_not_cut_obstacle_corners() {
+        let navmesh = grid(&[
+            "....",
+            ".#..",
+            "....",
+        ]);
+        let start = Vec3::new(0.5, 0.0, 0.5);
+        let goal = Vec3::new(2.5, 0.0, 2.5);
+        let path = navmesh.find_path(start, goal).unwrap();
+        assert_eq!(*path.last().unwrap(), goal);
+        assert_walkable_path(&navmesh, start, &path);
+    }
+
+    #[test]
+    fn walled_off_goal_is_unreachable() {
+        let navmesh = grid(&[
+            "......",
+            "######",
+            "......",
+        ]);
+        assert!(navmesh.find_path(Vec3::new(0.5, 0.0, 0.5), Vec3::new(0.5, 0.0, 2.5)).is_none());
+    }
+
+    #[test]
+    fn goal_inside_obstacle_ends_at_nearest_walkable_cell() {
+        let navmesh = grid(&[
+            ".....",
+            "...##",
+            "...##",
+        ]);
+        let path = navmesh.find_path(Vec3::new(0.5, 0.0, 0.5), Vec3::new(4.5, 0.0, 2.5)).unwrap();
+        let end = *path.last().unwrap();
+        let cell = navmesh.world_to_cell(end).unwrap();
+        assert!(navmesh.is_walkable(cell));
+        assert_eq!(end, navmesh.cell_center(cell));
+        // Ближайшие проходимые клетки - в двух клетках от цели
+        assert!((end.distance(Vec3::new(4.5, 0.0, 2.5)) - 2.0).abs() < 1e-4);
+    }
+}
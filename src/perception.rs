@@ -0,0 +1,151 @@
+// src/perception.rs - Восприятие врагов: зрение, слух и память о последней позиции игрока
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::player::Player;
+use crate::state::GameState;
+
+pub struct PerceptionPlugin;
+impl Plugin for PerceptionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NoiseEvent>()
+           .add_systems(Update, (
+               update_vision,
+               update_hearing,
+               decay_memory,
+           ).chain().in_set(PerceptionSet).run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Набор систем восприятия - ИИ должен работать после него
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PerceptionSet;
+
+/// Шум в мире (выстрел и т.п.), который могут услышать враги
+#[derive(Event)]
+pub struct NoiseEvent {
+    pub source: Entity,
+    pub position: Vec3,
+    pub radius: f32, // Дальность, на которой шум слышен
+}
+
+#[derive(Component)]
+pub struct Perception {
+    pub view_distance: f32,
+    pub fov: f32,                // Полный угол конуса обзора (радианы)
+    pub proximity_radius: f32,   // Вплотную игрока замечают даже спиной
+    pub hearing_multiplier: f32, // Множитель дальности слышимых шумов
+    pub memory_duration: f32,    // Сколько секунд помним последнюю позицию
+    pub eye_height: f32,
+    // Состояние
+    pub facing: Vec3,
+    pub sees_target: bool,
+    pub last_known_position: Option<Vec3>,
+    pub time_since_sensed: f32,
+}
+
+impl Default for Perception {
+    fn default() -> Self {
+        Self {
+            view_distance: 25.0,
+            fov: 120f32.to_radians(),
+            proximity_radius: 2.5,
+            hearing_multiplier: 1.0,
+            memory_duration: 5.0,
+            eye_height: 0.4,
+            facing: Vec3::NEG_Z,
+            sees_target: false,
+            last_known_position: None,
+            time_since_sensed: 0.0,
+        }
+    }
+}
+
+impl Perception {
+    fn remember(&mut self, position: Vec3) {
+        self.last_known_position = Some(position);
+        self.time_since_sensed = 0.0;
+    }
+}
+
+fn update_vision(
+    rapier_context: ReadRapierContext,
+    player_query: Query<&Transform, With<Player>>,
+    mut perceivers: Query<(Entity, &Transform, &mut Perception, Option<&Velocity>)>,
+) {
+    let player_transform = player_query.get_single().ok();
+    let rapier_context = rapier_context.single();
+
+    for (entity, transform, mut perception, velocity) in perceivers.iter_mut() {
+        // Смотрим туда, куда движемся
+        if let Some(velocity) = velocity {
+            let horizontal = Vec3::new(velocity.linvel.x, 0.0, velocity.linvel.z);
+            if horizontal.length_squared() > 0.01 {
+                perception.facing = horizontal.normalize();
+            }
+        }
+
+        perception.sees_target = false;
+        let Some(player_transform) = player_transform else {
+            continue;
+        };
+
+        let eye = transform.translation + Vec3::Y * perception.eye_height;
+        let to_player = player_transform.translation - eye;
+        let distance = to_player.length();
+        if distance > perception.view_distance || distance < f32::EPSILON {
+            continue;
+        }
+
+        let direction = to_player / distance;
+        let horizontal = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+        let in_cone = perception.facing.angle_between(horizontal) <= perception.fov * 0.5;
+        if !in_cone && distance > perception.proximity_radius {
+            continue;
+        }
+
+        // Луч проверяется только по статичной геометрии мира
+        let filter = QueryFilter::only_fixed().exclude_collider(entity);
+        let blocked = rapier_context
+            .cast_ray(eye, direction, distance, true, filter)
+            .is_some();
+
+        if !blocked {
+            perception.sees_target = true;
+            perception.remember(player_transform.translation);
+        }
+    }
+}
+
+fn update_hearing(
+    mut noise_events: EventReader<NoiseEvent>,
+    mut perceivers: Query<(Entity, &Transform, &mut Perception)>,
+) {
+    for noise in noise_events.read() {
+        for (entity, transform, mut perception) in perceivers.iter_mut() {
+            if entity == noise.source {
+                continue;
+            }
+            let hearing_range = noise.radius * perception.hearing_multiplier;
+            if transform.translation.distance(noise.position) <= hearing_range {
+                // Слышим, откуда стреляли, но не видим стрелка
+                perception.remember(noise.position);
+            }
+        }
+    }
+}
+
+fn decay_memory(
+    time: Res<Time>,
+    mut perceivers: Query<&mut Perception>,
+) {
+    for mut perception in perceivers.iter_mut() {
+        if perception.sees_target || perception.last_known_position.is_none() {
+            continue;
+        }
+        perception.time_since_sensed += time.delta_secs();
+        if perception.time_since_sensed > perception.memory_duration {
+            // Забываем игрока - враг теряет след
+            perception.last_known_position = None;
+        }
+    }
+}
@@ -0,0 +1,428 @@
+// src/pickups.rs - Подбираемые предметы: аптечки, броня, патроны, оружие и усиления.
+// Описаны в assets/pickups/items.pickups.ron, выпадают из врагов и лежат на арене
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+use crate::archetypes::{EnemyArchetype, EnemyArchetypes};
+use crate::data::RonAssetLoader;
+use crate::death::EnemyKilled;
+use crate::state::{GameState, InGame};
+use crate::enemies::Health;
+use crate::player::Player;
+use crate::powerups::PowerUpKind;
+use crate::stats::{ModifierOp, Stat};
+use crate::weapons::{Weapon, WeaponDefinition};
+
+pub struct PickupsPlugin;
+impl Plugin for PickupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<PickupCatalog>()
+           .register_asset_loader(RonAssetLoader::<PickupCatalog>::new(&["pickups.ron"]))
+           .add_event::<PickupCollected>()
+           .add_systems(Startup, load_pickups)
+           .add_systems(Update, (
+               drop_loot,
+               run_pickup_spawners,
+               collect_pickups,
+               expire_pickups,
+               animate_pickups,
+               log_pickups,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Предметы висят на этой высоте над точкой появления
+pub const PICKUP_HEIGHT: f32 = 0.8;
+// Выпавшая из врага добыча исчезает через это время (сек), последние секунды мигая
+const LOOT_LIFETIME: f32 = 20.0;
+const LOOT_BLINK_TIME: f32 = 3.0;
+// Как глубоко под местом гибели ищем пол для добычи
+const LOOT_FLOOR_PROBE: f32 = 20.0;
+
+/// Все типы предметов по имени
+#[derive(Asset, TypePath, Deserialize)]
+pub struct PickupCatalog {
+    pub items: HashMap<String, PickupDefinition>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PickupDefinition {
+    pub kind: PickupKind,
+    pub color: [f32; 3],
+    #[serde(default = "default_size")]
+    pub size: f32,
+}
+
+fn default_size() -> f32 {
+    0.5
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum PickupKind {
+    Health { amount: f32 },
+    Armor { amount: f32 },
+    Ammo { amount: u32 },
+    Weapon(WeaponDefinition),
+    PowerUp { power_up: PowerUpKind, duration: f32 },
+    /// Постоянная прибавка к характеристике, применяется в stats.rs
+    Upgrade { stat: Stat, op: ModifierOp },
+}
+
+impl PickupKind {
+    fn mesh(&self, size: f32) -> Mesh {
+        match self {
+            PickupKind::Health { .. } => Sphere::new(size * 0.5).into(),
+            PickupKind::Armor { .. } => Cuboid::new(size, size, size * 0.3).into(),
+            PickupKind::Ammo { .. } => Cuboid::new(size, size * 0.6, size * 0.6).into(),
+            PickupKind::Weapon(_) => Capsule3d::new(size * 0.2, size * 1.2).into(),
+            PickupKind::PowerUp { .. } => Torus::new(size * 0.3, size * 0.5).into(),
+            PickupKind::Upgrade { .. } => Cylinder::new(size * 0.5, size * 0.3).into(),
+        }
+    }
+}
+
+/// Игрок подобрал предмет. Усиления применяются по этому событию в powerups.rs
+#[derive(Event)]
+pub struct PickupCollected {
+    pub collector: Entity,
+    pub item: String,
+    pub kind: PickupKind,
+}
+
+#[derive(Component)]
+pub struct Pickup {
+    pub item: String,
+    pub kind: PickupKind,
+}
+
+/// Выпавшая добыча, которая со временем исчезает
+#[derive(Component)]
+pub struct PickupLifetime(pub f32);
+
+/// Вращающийся и покачивающийся меш предмета - дочерняя сущность,
+/// чтобы сенсор оставался на месте
+#[derive(Component)]
+struct PickupVisual {
+    phase: f32,
+}
+
+/// Место на арене, где предмет появляется снова через `respawn_time` после подбора
+#[derive(Component)]
+pub struct PickupSpawner {
+    pub item: String,
+    pub respawn_time: f32,
+    timer: f32,
+    current: Option<Entity>,
+}
+
+impl PickupSpawner {
+    pub fn new(item: &str, respawn_time: f32) -> Self {
+        Self {
+            item: item.to_string(),
+            respawn_time,
+            timer: 0.0,
+            current: None,
+        }
+    }
+
+    /// Сколько осталось до появления предмета и лежит ли он сейчас на месте
+    pub fn state(&self) -> (f32, Option<Entity>) {
+        (self.timer, self.current)
+    }
+
+    /// Восстановление из сохранения: `current` - уже заспавненный на месте предмет
+    pub fn restore(&mut self, timer: f32, current: Option<Entity>) {
+        self.timer = timer;
+        self.current = current;
+    }
+}
+
+#[derive(Resource)]
+pub struct Pickups {
+    catalog: Handle<PickupCatalog>,
+    // Меш и материал создаются один раз на тип предмета
+    visuals: HashMap<String, (Handle<Mesh>, Handle<StandardMaterial>)>,
+}
+
+impl Pickups {
+    pub fn is_ready(&self, catalogs: &Assets<PickupCatalog>) -> bool {
+        catalogs.contains(&self.catalog)
+    }
+
+    /// Есть ли такой предмет в загруженном каталоге
+    pub fn contains(&self, catalogs: &Assets<PickupCatalog>, item: &str) -> bool {
+        catalogs.get(&self.catalog).is_some_and(|catalog| catalog.items.contains_key(item))
+    }
+}
+
+fn load_pickups(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Pickups {
+        catalog: asset_server.load("pickups/items.pickups.ron"),
+        visuals: HashMap::new(),
+    });
+}
+
+pub trait PickupCommandsExt {
+    /// Спавнит предмет заданного типа. Если тип неизвестен, сущность удаляется
+    fn spawn_pickup(&mut self, item: &str, position: Vec3) -> EntityCommands<'_>;
+}
+
+impl PickupCommandsExt for Commands<'_, '_> {
+    fn spawn_pickup(&mut self, item: &str, position: Vec3) -> EntityCommands<'_> {
+        let item = item.to_string();
+        let mut entity = self.spawn_empty();
+        entity.queue(move |entity: Entity, world: &mut World| {
+            spawn_pickup(world, entity, &item, position);
+        });
+        entity
+    }
+}
+
+fn spawn_pickup(world: &mut World, entity: Entity, item: &str, position: Vec3) {
+    let definition = world.get_resource::<Pickups>()
+        .and_then(|pickups| world.resource::<Assets<PickupCatalog>>().get(&pickups.catalog))
+        .and_then(|catalog| catalog.items.get(item))
+        .cloned();
+    let Some(definition) = definition else {
+        warn!("Unknown pickup '{}'", item);
+        world.despawn(entity);
+        return;
+    };
+
+    let cached = world.resource::<Pickups>().visuals.get(item).cloned();
+    let (mesh, material) = cached.unwrap_or_else(|| {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(definition.kind.mesh(definition.size));
+        let [r, g, b] = definition.color;
+        let material = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::srgb(r, g, b),
+            emissive: LinearRgba::rgb(r, g, b) * 0.8,
+            ..default()
+        });
+        world.resource_mut::<Pickups>()
+            .visuals
+            .insert(item.to_string(), (mesh.clone(), material.clone()));
+        (mesh, material)
+    });
+
+    let Ok(mut entity) = world.get_entity_mut(entity) else {
+        return;
+    };
+    entity.insert((
+        Transform::from_translation(position + Vec3::Y * PICKUP_HEIGHT),
+        Visibility::default(),
+        Collider::ball(definition.size.max(0.6)),
+        Sensor,
+        // Сенсор без тела считается статичным - разрешаем ему пересекаться с кинематическим игроком
+        ActiveCollisionTypes::all(),
+        Pickup {
+            item: item.to_string(),
+            kind: definition.kind,
+        },
+        Name::new(format!("Pickup ({})", item)),
+        StateScoped(InGame),
+    ));
+    entity.with_children(|parent| {
+        parent.spawn((
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            Transform::default(),
+            PickupVisual { phase: position.x + position.z },
+        ));
+    });
+}
+
+/// Бросок по таблице добычи убитого врага
+fn drop_loot(
+    mut commands: Commands,
+    rapier_context: ReadRapierContext,
+    pickups: Res<Pickups>,
+    catalogs: Res<Assets<PickupCatalog>>,
+    archetypes: Res<EnemyArchetypes>,
+    archetype_assets: Res<Assets<EnemyArchetype>>,
+    mut killed: EventReader<EnemyKilled>,
+) {
+    let context = rapier_context.single();
+    let mut rng = rand::thread_rng();
+    for event in killed.read() {
+        let Some(archetype) = event.archetype.as_ref()
+            .and_then(|name| archetypes.get(name, &archetype_assets))
+        else {
+            continue;
+        };
+
+        let drops: Vec<&str> = archetype.loot
+            .iter()
+            .filter(|entry| rng.gen_range(0.0..1.0) < entry.chance)
+            .map(|entry| entry.item.as_str())
+            // Неизвестный предмет spawn_pickup удалит - не вешаем на него время жизни
+            .filter(|item| {
+                let known = pickups.contains(&catalogs, item);
+                if !known {
+                    warn!("Unknown loot item '{}'", item);
+                }
+                known
+            })
+            .collect();
+        // Несколько предметов раскладываем по кругу, чтобы они не слипались
+        for (i, item) in drops.iter().enumerate() {
+            let offset = if drops.len() > 1 {
+                let angle = TAU * i as f32 / drops.len() as f32;
+                Vec3::new(angle.cos(), 0.0, angle.sin()) * 0.8
+            } else {
+                Vec3::ZERO
+            };
+            // Добыча падает на пол под местом гибели - враг мог умереть на уступе или в прыжке
+            let start = event.position + offset;
+            let filter = QueryFilter::only_fixed().exclude_sensors();
+            let position = context
+                .cast_ray(start, Vec3::NEG_Y, LOOT_FLOOR_PROBE, true, filter)
+                .map_or(start, |(_, distance)| start - Vec3::Y * distance);
+            commands
+                .spawn_pickup(item, position)
+                .insert(PickupLifetime(LOOT_LIFETIME));
+        }
+    }
+}
+
+fn run_pickup_spawners(
+    mut commands: Commands,
+    time: Res<Time>,
+    pickups: Res<Pickups>,
+    catalogs: Res<Assets<PickupCatalog>>,
+    existing: Query<(), With<Pickup>>,
+    mut spawners: Query<(&Transform, &mut PickupSpawner)>,
+) {
+    // Пока каталог не загружен, спавнить нечего
+    if catalogs.get(&pickups.catalog).is_none() {
+        return;
+    }
+
+    for (transform, mut spawner) in spawners.iter_mut() {
+        if let Some(current) = spawner.current {
+            if existing.contains(current) {
+                continue;
+            }
+            // Предмет подобрали - запускаем отсчет до появления нового
+            spawner.current = None;
+            spawner.timer = spawner.respawn_time;
+        }
+        spawner.timer -= time.delta_secs();
+        if spawner.timer > 0.0 {
+            continue;
+        }
+        let pickup = commands.spawn_pickup(&spawner.item, transform.translation).id();
+        spawner.current = Some(pickup);
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    rapier_context: ReadRapierContext,
+    mut player_query: Query<(Entity, &mut Health, Option<&mut Weapon>), With<Player>>,
+    pickups: Query<(Entity, &Pickup)>,
+    mut collected: EventWriter<PickupCollected>,
+) {
+    let Ok((player, mut health, mut weapon)) = player_query.get_single_mut() else {
+        return;
+    };
+    let context = rapier_context.single();
+
+    for (entity, pickup) in pickups.iter() {
+        // Проверяем пересечение каждый кадр, а не по событию начала касания:
+        // иначе предмет, на котором игрок стоял с полным здоровьем, уже не подобрать
+        if context.intersection_pair(entity, player) != Some(true) {
+            continue;
+        }
+
+        let used = match &pickup.kind {
+            PickupKind::Health { amount } => health.heal(*amount),
+            PickupKind::Armor { amount } => health.add_armor(*amount),
+            PickupKind::Ammo { amount } => weapon.as_deref_mut().is_some_and(|weapon| {
+                match weapon.ammo.as_mut() {
+                    Some(ammo) if *ammo < weapon.max_ammo => {
+                        *ammo = (*ammo + amount).min(weapon.max_ammo);
+                        true
+                    }
+                    _ => false,
+                }
+            }),
+            PickupKind::Weapon(definition) => match weapon.as_deref_mut() {
+                Some(weapon) if weapon.name == definition.name => {
+                    // Такое оружие уже в руках - берем из него только патроны
+                    match weapon.ammo.as_mut() {
+                        Some(ammo) if *ammo < weapon.max_ammo => {
+                            *ammo = (*ammo + definition.ammo).min(weapon.max_ammo);
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                Some(weapon) => {
+                    let last_shot_time = weapon.last_shot_time;
+                    *weapon = definition.to_weapon();
+                    weapon.last_shot_time = last_shot_time;
+                    true
+                }
+                None => false,
+            },
+            PickupKind::PowerUp { .. } | PickupKind::Upgrade { .. } => true,
+        };
+        if !used {
+            continue;
+        }
+
+        collected.send(PickupCollected {
+            collector: player,
+            item: pickup.item.clone(),
+            kind: pickup.kind.clone(),
+        });
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn expire_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pickups: Query<(Entity, &mut PickupLifetime, &Children)>,
+    mut visuals: Query<&mut Visibility, With<PickupVisual>>,
+) {
+    for (entity, mut lifetime, children) in pickups.iter_mut() {
+        lifetime.0 -= time.delta_secs();
+        if lifetime.0 <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if lifetime.0 > LOOT_BLINK_TIME {
+            continue;
+        }
+        // Мигаем перед исчезновением
+        let visible = (lifetime.0 * 8.0).fract() > 0.3;
+        for child in children.iter() {
+            if let Ok(mut visibility) = visuals.get_mut(*child) {
+                *visibility = if visible { Visibility::Inherited } else { Visibility::Hidden };
+            }
+        }
+    }
+}
+
+fn animate_pickups(
+    time: Res<Time>,
+    mut visuals: Query<(&mut Transform, &PickupVisual)>,
+) {
+    let elapsed = time.elapsed_secs();
+    for (mut transform, visual) in visuals.iter_mut() {
+        transform.translation.y = (elapsed * 2.0 + visual.phase).sin() * 0.15;
+        transform.rotation = Quat::from_rotation_y(elapsed * 1.5 + visual.phase);
+    }
+}
+
+fn log_pickups(mut collected: EventReader<PickupCollected>) {
+    for event in collected.read() {
+        info!("{:?} picked up {} ({:?})", event.collector, event.item, event.kind);
+    }
+}
@@ -2,6 +2,13 @@
 use bevy::prelude::*;
 use bevy::input::keyboard::KeyCode;
 use bevy_rapier3d::prelude::*;
+use crate::enemies::Health;
+use crate::death::DEBRIS_GROUP;
+use crate::weapons::Faction;
+use crate::stats::{Stat, Stats};
+use crate::state::{GameState, InGame};
+use crate::level::{CurrentLevel, LevelDefinition};
+use crate::world::Launched;
 
 #[derive(Resource)]
 pub struct GameSettings {
@@ -12,6 +19,31 @@ pub struct GameSettings {
     pub sv_jump_force: f32,
 }
 
+impl GameSettings {
+    pub fn cvar(&self, name: &str) -> Option<f32> {
+        match name {
+            "sv_maxspeed" => Some(self.sv_maxspeed),
+            "sv_accelerate" => Some(self.sv_accelerate),
+            "sv_air_accelerate" => Some(self.sv_air_accelerate),
+            "sv_gravity" => Some(self.sv_gravity),
+            "sv_jump_force" => Some(self.sv_jump_force),
+            _ => None,
+        }
+    }
+
+    /// Переменная по имени, как в консоли Quake
+    pub fn cvar_mut(&mut self, name: &str) -> Option<&mut f32> {
+        match name {
+            "sv_maxspeed" => Some(&mut self.sv_maxspeed),
+            "sv_accelerate" => Some(&mut self.sv_accelerate),
+            "sv_air_accelerate" => Some(&mut self.sv_air_accelerate),
+            "sv_gravity" => Some(&mut self.sv_gravity),
+            "sv_jump_force" => Some(&mut self.sv_jump_force),
+            _ => None,
+        }
+    }
+}
+
 impl Default for GameSettings {
     fn default() -> Self {
         Self {
@@ -24,6 +56,115 @@ impl Default for GameSettings {
     }
 }
 
+/// Клавиша или кнопка мыши, назначенная действию
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    pub fn pressed(&self, keyboard: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>) -> bool {
+        match *self {
+            Binding::Key(key) => keyboard.pressed(key),
+            Binding::Mouse(button) => mouse.pressed(button),
+        }
+    }
+
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>) -> bool {
+        match *self {
+            Binding::Key(key) => keyboard.just_pressed(key),
+            Binding::Mouse(button) => mouse.just_pressed(button),
+        }
+    }
+
+    /// Имя для меню: "W", "Space", "Mouse Left"
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{:?}", key);
+                name.strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name)
+                    .to_string()
+            }
+            Binding::Mouse(button) => format!("Mouse {:?}", button),
+        }
+    }
+}
+
+/// Действия игрока, которые можно переназначить в меню
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Control {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Jump,
+    Fire,
+}
+
+impl Control {
+    pub const ALL: [Control; 6] = [
+        Control::Forward,
+        Control::Back,
+        Control::Left,
+        Control::Right,
+        Control::Jump,
+        Control::Fire,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Control::Forward => "Forward",
+            Control::Back => "Back",
+            Control::Left => "Strafe left",
+            Control::Right => "Strafe right",
+            Control::Jump => "Jump",
+            Control::Fire => "Fire",
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct KeyBindings([Binding; Control::ALL.len()]);
+
+impl KeyBindings {
+    pub fn get(&self, control: Control) -> Binding {
+        self.0[control as usize]
+    }
+
+    /// Назначает действию клавишу. Если она уже занята другим действием, оно получает прежнюю клавишу этого
+    pub fn set(&mut self, control: Control, binding: Binding) {
+        let previous = self.get(control);
+        if let Some(other) = self.0.iter_mut().find(|other| **other == binding) {
+            *other = previous;
+        }
+        self.0[control as usize] = binding;
+    }
+
+    pub fn pressed(&self, control: Control, keyboard: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>) -> bool {
+        self.get(control).pressed(keyboard, mouse)
+    }
+
+    pub fn just_pressed(&self, control: Control, keyboard: &ButtonInput<KeyCode>, mouse: &ButtonInput<MouseButton>) -> bool {
+        self.get(control).just_pressed(keyboard, mouse)
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self([
+            Binding::Key(KeyCode::KeyW),
+            Binding::Key(KeyCode::KeyS),
+            Binding::Key(KeyCode::KeyA),
+            Binding::Key(KeyCode::KeyD),
+            Binding::Key(KeyCode::Space),
+            Binding::Mouse(MouseButton::Left),
+        ])
+    }
+}
+
 #[derive(Component)]
 pub struct Player;
 
@@ -80,16 +221,17 @@ pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(GameSettings::default())
-            .add_systems(Startup, spawn_player)
+            .init_resource::<KeyBindings>()
+            .add_systems(OnEnter(InGame), spawn_player)
             .add_systems(Update, (
                 handle_input,
                 debug_player_position,
                 emergency_respawn,
-            ))
+            ).run_if(in_state(GameState::Playing)))
             .add_systems(FixedUpdate, (
                     apply_acceleration_cpma,
                     move_kinematic_player_by_velocity,
-            ).chain());
+            ).chain().run_if(in_state(GameState::Playing)));
     }
 }
 
@@ -97,6 +239,8 @@ pub fn spawn_player(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
 ) {
     let player = commands
         .spawn(PlayerBundle {
@@ -105,8 +249,8 @@ pub fn spawn_player(
             velocity: Velocity::default(),
             wish_direction: WishDirection::default(),
             wish_speed: WishSpeed::default(),
-            // ВАЖНО: Поднимаем игрока значительно выше пола
-            transform: Transform::from_xyz(0.0, 3.0, 0.0), // Было 1.0, стало 3.0
+            // ВАЖНО: точка старта в файле уровня должна быть заметно выше пола
+            transform: Transform::from_translation(level.player_start(&levels)),
             rigid_body: RigidBody::KinematicPositionBased,
             // Уменьшаем коллайдер для лучшей совместимости
             collider: Collider::capsule_y(0.5, 0.3), // высота 0.5, радиус 0.3
@@ -131,12 +275,13 @@ pub fn spawn_player(
         .id();
 
     commands.entity(player)
-        .insert(ActiveEvents::COLLISION_EVENTS)
+        .insert((ActiveEvents::COLLISION_EVENTS, StateScoped(InGame)))
+        .insert((Health::new(100.0), Faction::Player, Stats::default()))
         .insert(KinematicCharacterController {
             offset: CharacterLength::Absolute(0.02), // Увеличили offset
             up: Vec3::Y,
-            max_slope_climb_angle: 0.785398, // 45 градусов
-            min_slope_slide_angle: 0.785398,
+            max_slope_climb_angle: std::f32::consts::FRAC_PI_4, // 45 градусов
+            min_slope_slide_angle: std::f32::consts::FRAC_PI_4,
             slide: false,
             apply_impulse_to_dynamic_bodies: true,
             autostep: Some(CharacterAutostep {
@@ -144,6 +289,8 @@ pub fn spawn_player(
                 min_width: CharacterLength::Absolute(0.05),
                 include_dynamic_bodies: false,
             }),
+            // Обломки врагов не мешают двигаться
+            filter_groups: Some(CollisionGroups::new(Group::ALL, Group::ALL - DEBRIS_GROUP)),
             ..default()
         });
 }
@@ -152,11 +299,12 @@ pub fn spawn_player(
 fn debug_player_position(
     player_query: Query<(&Transform, &Velocity), With<Player>>,
 ) {
-    if let Ok((transform, velocity)) = player_query.get_single() {
-        if transform.translation.y < -5.0 {
-            println!("ПРЕДУПРЕЖДЕНИЕ: Игрок провалился! Позиция: {:?}, Скорость: {:?}", 
-                transform.translation, velocity.0);
-        }
+    let Ok((transform, velocity)) = player_query.get_single() else {
+        return;
+    };
+    if transform.translation.y < -5.0 {
+        println!("ПРЕДУПРЕЖДЕНИЕ: Игрок провалился! Позиция: {:?}, Скорость: {:?}", 
+            transform.translation, velocity.0);
     }
 }
 
@@ -170,9 +318,11 @@ pub fn apply_acceleration_cpma(
         &mut Jump,
         &Transform,
         Option<&KinematicCharacterControllerOutput>, // Делаем опциональным
+        Option<&Stats>,
+        Has<Launched>,
     ), With<Player>>,
 ) {
-    for (mut velocity, wish_dir, wish_speed, mut jump, transform, kcc_output) in query.iter_mut() {
+    for (mut velocity, wish_dir, wish_speed, mut jump, transform, kcc_output, stats, launched) in query.iter_mut() {
         let dt = time.delta_secs();
         
         // Проверяем, находимся ли мы на земле
@@ -185,7 +335,9 @@ pub fn apply_acceleration_cpma(
 
         let mut vel = velocity.0;
         let wish_dir = wish_dir.0.normalize_or_zero();
-        let wish_speed = wish_speed.0;
+        // Модификаторы скорости поднимают и предельную, и желаемую скорость
+        let max_speed = stats.map_or(settings.sv_maxspeed, |stats| stats.apply(Stat::MoveSpeed, settings.sv_maxspeed));
+        let wish_speed = wish_speed.0 * max_speed / settings.sv_maxspeed;
 
         // Применяем ускорение только если есть желаемое направление
         if wish_dir != Vec3::ZERO {
@@ -205,11 +357,11 @@ pub fn apply_acceleration_cpma(
             }
         }
 
-        // Ограничиваем горизонтальную скорость только на земле
-        if on_ground {
+        // Ограничиваем горизонтальную скорость только на земле и не сразу после ускорителя
+        if on_ground && !launched {
             let horizontal_speed = Vec3::new(vel.x, 0.0, vel.z).length();
-            if horizontal_speed > settings.sv_maxspeed {
-                let scale = settings.sv_maxspeed / horizontal_speed;
+            if horizontal_speed > max_speed {
+                let scale = max_speed / horizontal_speed;
                 vel.x *= scale;
                 vel.z *= scale;
             }
@@ -273,31 +425,30 @@ pub fn move_kinematic_player_by_velocity(
 
 pub fn handle_input(
     keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    bindings: Res<KeyBindings>,
     mut query: Query<(&mut WishDirection, &mut WishSpeed, &mut Jump), With<Player>>,
 ) {
     for (mut wish_dir, mut wish_speed, mut jump) in query.iter_mut() {
         let mut direction = Vec3::ZERO;
 
-        // Стандартное WASD управление
-        if keyboard.pressed(KeyCode::KeyW) {
+        // По умолчанию WASD, клавиши переназначаются в меню
+        let pressed = |control| bindings.pressed(control, &keyboard, &mouse);
+        if pressed(Control::Forward) {
             direction.z -= 1.0;
         }
-        if keyboard.pressed(KeyCode::KeyS) {
+        if pressed(Control::Back) {
             direction.z += 1.0;
         }
-        if keyboard.pressed(KeyCode::KeyA) {
+        if pressed(Control::Left) {
             direction.x -= 1.0;
         }
-        if keyboard.pressed(KeyCode::KeyD) {
+        if pressed(Control::Right) {
             direction.x += 1.0;
         }
 
         // Прыжок
-        if keyboard.just_pressed(KeyCode::Space) {
-            jump.is_jumping = true;
-        } else {
-            jump.is_jumping = false;
-        }
+        jump.is_jumping = bindings.just_pressed(Control::Jump, &keyboard, &mouse);
 
         wish_dir.0 = direction.normalize_or_zero();
         wish_speed.0 = if direction.length_squared() > 0.0 { 8.0 } else { 0.0 };
@@ -307,19 +458,22 @@ pub fn handle_input(
 pub fn emergency_respawn(
     mut player_query: Query<(&mut Transform, &mut Velocity), With<Player>>,
     keyboard: Res<ButtonInput<KeyCode>>,
+    level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
 ) {
     if let Ok((mut transform, mut velocity)) = player_query.get_single_mut() {
+        let start = level.player_start(&levels);
         // Автоматическое восстановление если игрок провалился
         if transform.translation.y < -2.0 {
             println!("Игрок провалился! Восстанавливаем позицию...");
-            transform.translation = Vec3::new(0.0, 3.0, 0.0);
+            transform.translation = start;
             velocity.0 = Vec3::ZERO;
         }
         
         // Ручное восстановление на R
         if keyboard.just_pressed(KeyCode::KeyR) {
             println!("Ручное восстановление позиции игрока");
-            transform.translation = Vec3::new(0.0, 3.0, 0.0);
+            transform.translation = start;
             velocity.0 = Vec3::ZERO;
         }
     }
@@ -0,0 +1,170 @@
+// src/powerups.rs - Временные усиления: квад, ускорение, регенерация.
+// Подбираются как предметы и превращаются в модификаторы характеристик (stats.rs)
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::pickups::{PickupCollected, PickupKind};
+use crate::state::GameState;
+use crate::stats::{ModifierOp, ModifierSource, Stacking, Stat, StatModifier, Stats};
+
+pub struct PowerUpsPlugin;
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (
+               apply_power_up_pickups,
+               update_power_up_auras,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Повторный подбор продлевает усиление, но не дольше этого (сек)
+const MAX_DURATION: f32 = 60.0;
+// Последние секунды аура мигает - усиление скоро кончится
+const EXPIRY_WARNING: f32 = 3.0;
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PowerUpKind {
+    /// Урон оружия x4
+    QuadDamage,
+    /// Быстрее бег и стрельба
+    Haste,
+    /// Постепенное лечение до максимума
+    Regeneration,
+}
+
+impl PowerUpKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PowerUpKind::QuadDamage => "QUAD DAMAGE",
+            PowerUpKind::Haste => "HASTE",
+            PowerUpKind::Regeneration => "REGENERATION",
+        }
+    }
+
+    pub fn color(&self) -> LinearRgba {
+        match self {
+            PowerUpKind::QuadDamage => LinearRgba::rgb(0.3, 0.4, 1.0),
+            PowerUpKind::Haste => LinearRgba::rgb(1.0, 0.8, 0.1),
+            PowerUpKind::Regeneration => LinearRgba::rgb(1.0, 0.15, 0.15),
+        }
+    }
+
+    fn modifiers(&self) -> Vec<(Stat, ModifierOp)> {
+        match self {
+            PowerUpKind::QuadDamage => vec![(Stat::Damage, ModifierOp::Multiply(4.0))],
+            PowerUpKind::Haste => vec![
+                (Stat::MoveSpeed, ModifierOp::Multiply(1.3)),
+                (Stat::FireRate, ModifierOp::Multiply(1.5)),
+            ],
+            PowerUpKind::Regeneration => vec![(Stat::HealthRegen, ModifierOp::Add(10.0))],
+        }
+    }
+}
+
+/// Действующие усиления и сколько им осталось
+pub fn active_power_ups(stats: &Stats) -> Vec<(PowerUpKind, f32)> {
+    stats.sources()
+        .into_iter()
+        .filter_map(|source| match source {
+            ModifierSource::PowerUp(kind) => Some((kind, stats.remaining(source).unwrap_or(0.0))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Светящаяся оболочка вокруг сущности с усилениями
+#[derive(Component)]
+struct PowerUpAura;
+
+fn apply_power_up_pickups(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut collected: EventReader<PickupCollected>,
+    mut holders: Query<(&mut Stats, Option<&Children>)>,
+    auras: Query<(), With<PowerUpAura>>,
+) {
+    for event in collected.read() {
+        let PickupKind::PowerUp { power_up, duration } = event.kind else {
+            continue;
+        };
+        let Ok((mut stats, children)) = holders.get_mut(event.collector) else {
+            continue;
+        };
+        info!("{} for {:.0}s", power_up.label(), duration);
+
+        // Одинаковые усиления не суммируются по силе - повторный подбор продлевает время
+        for (stat, op) in power_up.modifiers() {
+            stats.add(
+                StatModifier::new(stat, op, ModifierSource::PowerUp(power_up)).with_duration(duration),
+                Stacking::Extend { max_duration: MAX_DURATION },
+            );
+        }
+
+        let has_aura = children.is_some_and(|children| children.iter().any(|child| auras.contains(*child)));
+        if has_aura {
+            continue;
+        }
+        let aura = commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(0.9))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::NONE,
+                alpha_mode: AlphaMode::Add,
+                unlit: true,
+                ..default()
+            })),
+            Transform::default(),
+            PointLight {
+                intensity: 0.0,
+                range: 6.0,
+                shadows_enabled: false,
+                ..default()
+            },
+            PowerUpAura,
+            Name::new("Power-up Aura"),
+        )).id();
+        commands.entity(event.collector).add_child(aura);
+    }
+}
+
+fn update_power_up_auras(
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    holders: Query<(&Stats, &Children)>,
+    mut auras: Query<(&MeshMaterial3d<StandardMaterial>, &mut PointLight, &mut Visibility), With<PowerUpAura>>,
+) {
+    let elapsed = time.elapsed_secs();
+    for (stats, children) in holders.iter() {
+        for child in children.iter() {
+            let Ok((material, mut light, mut visibility)) = auras.get_mut(*child) else {
+                continue;
+            };
+            let active = active_power_ups(stats);
+            if active.is_empty() {
+                *visibility = Visibility::Hidden;
+                continue;
+            }
+
+            // Цвета действующих усилений смешиваются
+            let mut color = LinearRgba::BLACK;
+            for (kind, _) in &active {
+                color += kind.color();
+            }
+            color *= 1.0 / active.len() as f32;
+
+            // Мигаем, когда хоть одно усиление вот-вот кончится
+            let expiring = active.iter().any(|(_, remaining)| *remaining < EXPIRY_WARNING);
+            let pulse = if expiring {
+                if (elapsed * 6.0).fract() < 0.5 { 0.2 } else { 1.0 }
+            } else {
+                0.75 + 0.25 * (elapsed * 3.0).sin()
+            };
+
+            *visibility = Visibility::Inherited;
+            light.color = Color::LinearRgba(color);
+            light.intensity = 150_000.0 * pulse;
+            if let Some(material) = materials.get_mut(&material.0) {
+                material.base_color = Color::LinearRgba(color * (0.25 * pulse));
+            }
+        }
+    }
+}
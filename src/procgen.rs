@@ -0,0 +1,485 @@
+// src/procgen.rs - Генератор арен по зерну: платформы на нескольких высотах, пандусы,
+// прыжковые площадки, укрытия, точки спавна и предметы. Достижимость платформ проверяется по параметрам
+// движения игрока по умолчанию (`GameSettings::default()`), а не по текущим переменным консоли -
+// одно и то же зерно всегда дает одну и ту же арену.
+// Консольная команда `genmap [seed]`
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::console::{Console, ConsoleAppExt, ConsoleCommand};
+use crate::level::{
+    Brush, BrushShape, CurrentLevel, LevelDefinition, LevelJumpPad, LevelLight, LevelMaterial,
+    LevelPickup, LevelSpawnPoint, LightKind,
+};
+use crate::player::GameSettings;
+use crate::state::RestartLevel;
+use crate::world::Affects;
+
+pub struct ProcGenPlugin;
+impl Plugin for ProcGenPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_console_command("genmap", "genmap [seed] - generate a random arena")
+           .add_systems(Update, genmap_command);
+    }
+}
+
+/// Имя сгенерированного уровня (в таблицах рекордов и сохранениях)
+pub const GENERATED_LEVEL: &str = "generated";
+
+// Высоты платформ: нижняя берется прыжком, остальные - по пандусу, площадке или с соседней платформы
+const PLATFORM_HEIGHTS: [f32; 3] = [1.0, 2.0, 3.0];
+// Уклон пандусов - заметно положе предельного для контроллера игрока (45°)
+const RAMP_ANGLE_DEGREES: f32 = 25.0;
+const RAMP_THICKNESS: f32 = 0.2;
+// Свободное место вокруг центра, где появляется игрок
+const START_CLEARANCE: f32 = 6.0;
+// Полоса вдоль края арены под точки спавна врагов
+const EDGE_MARGIN: f32 = 4.0;
+// Проходы между препятствиями должны пропускать врагов
+const MIN_GAP: f32 = 2.0;
+// Запас по прыжку: не всякий прыжок выполняется на полной скорости и с идеального места
+const JUMP_SAFETY: f32 = 0.6;
+const JUMP_HEIGHT_MARGIN: f32 = 0.2;
+
+/// Прямоугольник препятствия на плане арены
+#[derive(Clone, Copy)]
+struct Footprint {
+    center: Vec2,
+    half_size: Vec2,
+}
+
+impl Footprint {
+    fn overlaps(&self, other: &Footprint, gap: f32) -> bool {
+        let distance = (self.center - other.center).abs();
+        distance.x < self.half_size.x + other.half_size.x + gap
+            && distance.y < self.half_size.y + other.half_size.y + gap
+    }
+
+    /// Расстояние между краями по горизонтали, 0 - если касаются
+    fn gap(&self, other: &Footprint) -> f32 {
+        let distance = (self.center - other.center).abs() - self.half_size - other.half_size;
+        distance.max(Vec2::ZERO).length()
+    }
+}
+
+struct Platform {
+    footprint: Footprint,
+    height: f32,
+    /// Пандус с пола, если платформа слишком высока для прыжка
+    ramp: Option<(Footprint, Brush)>,
+    /// Или прыжковая площадка рядом с ней
+    jump_pad: Option<(Footprint, LevelJumpPad)>,
+}
+
+impl Platform {
+    /// Место, занятое пандусом или площадкой
+    fn access(&self) -> Option<Footprint> {
+        self.ramp.as_ref().map(|(footprint, _)| *footprint)
+            .or(self.jump_pad.as_ref().map(|(footprint, _)| *footprint))
+    }
+}
+
+/// Параметры прыжка игрока
+struct JumpReach {
+    height: f32,
+    speed: f32,
+    gravity: f32,
+    jump_force: f32,
+}
+
+impl JumpReach {
+    fn new(settings: &GameSettings) -> Self {
+        let gravity = settings.sv_gravity.abs();
+        Self {
+            height: settings.sv_jump_force * settings.sv_jump_force / (2.0 * gravity),
+            speed: settings.sv_maxspeed,
+            gravity,
+            jump_force: settings.sv_jump_force,
+        }
+    }
+
+    /// Можно ли перепрыгнуть с высоты `from` на высоту `to` через зазор `gap`
+    fn can_jump(&self, from: f32, to: f32, gap: f32) -> bool {
+        let rise = to - from;
+        if rise > self.height - JUMP_HEIGHT_MARGIN {
+            return false;
+        }
+        // Время до приземления на нужной высоте - нисходящий корень y(t) = v*t - g*t^2/2
+        let v = self.jump_force;
+        let discriminant = v * v - 2.0 * self.gravity * rise.max(0.0);
+        let time = (v + discriminant.max(0.0).sqrt()) / self.gravity;
+        gap <= self.speed * time * JUMP_SAFETY
+    }
+}
+
+/// Собирает арену по зерну
+pub fn generate_arena(seed: u64) -> LevelDefinition {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let reach = JumpReach::new(&GameSettings::default());
+    let half = rng.gen_range(20..=30) as f32;
+
+    let mut platforms: Vec<Platform> = Vec::new();
+    let mut occupied: Vec<Footprint> = Vec::new();
+    let platform_count = rng.gen_range(3..=6);
+    let mut attempts = 0;
+    while platforms.len() < platform_count && attempts < 200 {
+        attempts += 1;
+        let half_size = Vec2::new(rng.gen_range(2.0..4.0), rng.gen_range(2.0..4.0));
+        let Some(footprint) = random_footprint(&mut rng, half, half_size, &occupied) else {
+            continue;
+        };
+        let height = PLATFORM_HEIGHTS[rng.gen_range(0..PLATFORM_HEIGHTS.len())];
+        let mut platform = Platform { footprint, height, ramp: None, jump_pad: None };
+        if !reach.can_jump(0.0, height, 0.0) {
+            // Пандус или площадка поровну; если одному не хватило места - пробуем другое
+            if rng.gen_bool(0.5) {
+                platform.ramp = place_ramp(&mut rng, &platform, half, &occupied);
+            }
+            if platform.ramp.is_none() {
+                platform.jump_pad = place_jump_pad(&mut rng, &platform, half, &occupied);
+            }
+            if platform.ramp.is_none() && platform.jump_pad.is_none() {
+                platform.ramp = place_ramp(&mut rng, &platform, half, &occupied);
+            }
+        }
+        occupied.push(footprint);
+        occupied.extend(platform.access());
+        platforms.push(platform);
+    }
+
+    // Проверка достижимости: с пола по пандусам и прыжкам, дальше с платформы на платформу
+    let mut reachable: Vec<bool> = platforms
+        .iter()
+        .map(|platform| platform.access().is_some() || reach.can_jump(0.0, platform.height, 0.0))
+        .collect();
+    loop {
+        let mut changed = false;
+        for i in 0..platforms.len() {
+            if reachable[i] {
+                continue;
+            }
+            let from_neighbor = (0..platforms.len()).any(|j| {
+                reachable[j] && reach.can_jump(
+                    platforms[j].height,
+                    platforms[i].height,
+                    platforms[j].footprint.gap(&platforms[i].footprint),
+                )
+            });
+            if from_neighbor {
+                reachable[i] = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let unreachable = reachable.iter().filter(|reachable| !**reachable).count();
+    if unreachable > 0 {
+        debug!("Arena {}: dropped {} unreachable platforms", seed, unreachable);
+    }
+    let platforms: Vec<Platform> = platforms
+        .into_iter()
+        .zip(reachable)
+        .filter_map(|(platform, reachable)| reachable.then_some(platform))
+        .collect();
+    // Пересобираем занятые клетки без выброшенных платформ
+    let mut occupied: Vec<Footprint> = platforms
+        .iter()
+        .flat_map(|platform| std::iter::once(platform.footprint).chain(platform.access()))
+        .collect();
+
+    let mut brushes = vec![Brush {
+        shape: BrushShape::Plane { size: [half * 2.0, half * 2.0] },
+        position: [0.0, 0.0, 0.0],
+        rotation: [0.0; 3],
+        material: "floor".to_string(),
+        solid: true,
+        ground: true,
+    }];
+    for platform in &platforms {
+        let size = platform.footprint.half_size * 2.0;
+        brushes.push(Brush {
+            shape: BrushShape::Box { size: [size.x, platform.height, size.y] },
+            position: [platform.footprint.center.x, platform.height * 0.5, platform.footprint.center.y],
+            rotation: [0.0; 3],
+            material: "platform".to_string(),
+            solid: true,
+            ground: false,
+        });
+        if let Some((_, ramp)) = &platform.ramp {
+            brushes.push(ramp.clone());
+        }
+    }
+    let jump_pads: Vec<LevelJumpPad> = platforms
+        .iter()
+        .filter_map(|platform| platform.jump_pad.as_ref().map(|(_, pad)| pad.clone()))
+        .collect();
+
+    // Укрытия на полу
+    let cover_count = rng.gen_range(6..=12);
+    for _ in 0..cover_count {
+        let half_size = Vec2::new(rng.gen_range(0.5..1.25), rng.gen_range(0.5..1.25));
+        let Some(footprint) = random_footprint(&mut rng, half, half_size, &occupied) else {
+            continue;
+        };
+        let height = rng.gen_range(1.2..2.2);
+        occupied.push(footprint);
+        brushes.push(Brush {
+            shape: BrushShape::Box { size: [half_size.x * 2.0, height, half_size.y * 2.0] },
+            position: [footprint.center.x, height * 0.5, footprint.center.y],
+            rotation: [0.0; 3],
+            material: "cover".to_string(),
+            solid: true,
+            ground: false,
+        });
+    }
+
+    // Точки спавна в полосе у края - туда препятствия не ставятся.
+    // Группы те же, что на стандартной арене, чтобы подходили ее волны
+    let edge = half - EDGE_MARGIN * 0.5;
+    let mut spawn_points = Vec::new();
+    for offset in [-0.5, 0.0, 0.5] {
+        spawn_points.push(spawn_point([offset * half, 1.0, -edge], "north"));
+        spawn_points.push(spawn_point([offset * half, 1.0, edge], "south"));
+    }
+    spawn_points.push(spawn_point([-edge, 1.0, 0.0], "west"));
+    spawn_points.push(spawn_point([edge, 1.0, 0.0], "east"));
+    for (x, z) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+        spawn_points.push(spawn_point([x * edge, 1.0, z * edge], "corners"));
+    }
+
+    // Ценные предметы - на самых высоких платформах, за них надо полазить
+    let mut pickups = Vec::new();
+    let mut by_height: Vec<&Platform> = platforms.iter().collect();
+    by_height.sort_by(|a, b| b.height.total_cmp(&a.height));
+    let rewards = [("quad_damage", 90.0), ("haste", 60.0), ("armor", 30.0), ("health_large", 25.0)];
+    for (platform, (item, respawn_time)) in by_height.iter().zip(rewards) {
+        pickups.push(LevelPickup {
+            item: item.to_string(),
+            position: [platform.footprint.center.x, platform.height, platform.footprint.center.y],
+            respawn_time,
+        });
+    }
+    let floor_items = [("ammo", 15.0), ("ammo", 15.0), ("health_small", 15.0), ("health_small", 15.0), ("shotgun", 30.0)];
+    for (item, respawn_time) in floor_items {
+        let Some(footprint) = random_footprint(&mut rng, half, Vec2::splat(0.5), &occupied) else {
+            continue;
+        };
+        occupied.push(footprint);
+        pickups.push(LevelPickup {
+            item: item.to_string(),
+            position: [footprint.center.x, 0.0, footprint.center.y],
+            respawn_time,
+        });
+    }
+
+    LevelDefinition {
+        player_start: [0.0, 3.0, 0.0],
+        waves: "waves/arena.waves.ron".to_string(),
+        materials: HashMap::from([
+            ("floor".to_string(), material([0.45, 0.45, 0.5], 0.9)),
+            ("platform".to_string(), material([0.55, 0.35, 0.2], 0.7)),
+            ("ramp".to_string(), material([0.6, 0.5, 0.3], 0.7)),
+            ("cover".to_string(), material([0.2, 0.3, 0.8], 0.5)),
+        ]),
+        brushes,
+        models: Vec::new(),
+        lights: vec![LevelLight {
+            kind: LightKind::Directional { illuminance: 50.0, shadows: true },
+            position: [14.0, 10.0, 4.0],
+            look_at: [0.0, 0.0, 0.0],
+        }],
+        spawn_points,
+        pickups,
+        jump_pads,
+        teleporters: Vec::new(),
+        boost_strips: Vec::new(),
+        movers: Vec::new(),
+        hazards: Vec::new(),
+        props: Vec::new(),
+        triggers: Vec::new(),
+        scripts: Vec::new(),
+    }
+}
+
+/// Случайное свободное место внутри арены, не у края и не в центре
+fn random_footprint(rng: &mut StdRng, half: f32, half_size: Vec2, occupied: &[Footprint]) -> Option<Footprint> {
+    let limit = half - EDGE_MARGIN - half_size.max_element();
+    if limit <= 0.0 {
+        return None;
+    }
+    for _ in 0..30 {
+        let center = Vec2::new(rng.gen_range(-limit..limit), rng.gen_range(-limit..limit));
+        let footprint = Footprint { center, half_size };
+        let start = Footprint { center: Vec2::ZERO, half_size: Vec2::splat(START_CLEARANCE) };
+        if footprint.overlaps(&start, 0.0) || occupied.iter().any(|other| footprint.overlaps(other, MIN_GAP)) {
+            continue;
+        }
+        return Some(footprint);
+    }
+    None
+}
+
+/// Пандус с пола к одной из сторон платформы, которая смотрит на свободное место
+fn place_ramp(rng: &mut StdRng, platform: &Platform, half: f32, occupied: &[Footprint]) -> Option<(Footprint, Brush)> {
+    let angle = RAMP_ANGLE_DEGREES.to_radians();
+    let run = platform.height / angle.tan();
+    let length = platform.height / angle.sin();
+    let width = 2.0;
+    for side in shuffled_sides(rng) {
+        let extent = (platform.footprint.half_size * side).abs().max_element();
+        let center = platform.footprint.center + side * (extent + run * 0.5);
+        let half_size = if side.x != 0.0 { Vec2::new(run * 0.5, width * 0.5) } else { Vec2::new(width * 0.5, run * 0.5) };
+        let footprint = Footprint { center, half_size };
+        let inside = center.abs().max_element() + half_size.max_element() < half - EDGE_MARGIN;
+        if !inside || occupied.iter().any(|other| footprint.overlaps(other, MIN_GAP)) {
+            continue;
+        }
+        // Наклонная плита: верхний край у платформы, нижний - на полу
+        let tilt = angle.to_degrees();
+        let rotation = if side.x > 0.0 {
+            [0.0, 0.0, -tilt]
+        } else if side.x < 0.0 {
+            [0.0, 0.0, tilt]
+        } else if side.y > 0.0 {
+            [tilt, 0.0, 0.0]
+        } else {
+            [-tilt, 0.0, 0.0]
+        };
+        let size = if side.x != 0.0 { [length, RAMP_THICKNESS, width] } else { [width, RAMP_THICKNESS, length] };
+        let brush = Brush {
+            shape: BrushShape::Box { size },
+            position: [center.x, platform.height * 0.5, center.y],
+            rotation,
+            material: "ramp".to_string(),
+            solid: true,
+            ground: false,
+        };
+        return Some((footprint, brush));
+    }
+    None
+}
+
+/// Прыжковая площадка на полу у одной из сторон платформы, бросает на середину платформы
+fn place_jump_pad(rng: &mut StdRng, platform: &Platform, half: f32, occupied: &[Footprint]) -> Option<(Footprint, LevelJumpPad)> {
+    let radius = 1.0;
+    for side in shuffled_sides(rng) {
+        let extent = (platform.footprint.half_size * side).abs().max_element();
+        let center = platform.footprint.center + side * (extent + MIN_GAP + radius);
+        let footprint = Footprint { center, half_size: Vec2::splat(radius) };
+        let inside = center.abs().max_element() + radius < half - EDGE_MARGIN;
+        if !inside || occupied.iter().any(|other| footprint.overlaps(other, MIN_GAP)) {
+            continue;
+        }
+        let target = platform.footprint.center;
+        // Цель - центр игрока, стоящего на платформе
+        let pad = LevelJumpPad {
+            position: [center.x, 0.05, center.y],
+            target: [target.x, platform.height + 1.0, target.y],
+            radius,
+            affects: Affects::default(),
+        };
+        return Some((footprint, pad));
+    }
+    None
+}
+
+/// Стороны платформы в случайном порядке, чтобы пандусы и площадки не смотрели все в одну сторону
+fn shuffled_sides(rng: &mut StdRng) -> [Vec2; 4] {
+    let mut sides = [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y];
+    for i in (1..sides.len()).rev() {
+        sides.swap(i, rng.gen_range(0..=i));
+    }
+    sides
+}
+
+fn spawn_point(position: [f32; 3], group: &str) -> LevelSpawnPoint {
+    LevelSpawnPoint { position, group: group.to_string() }
+}
+
+fn material(color: [f32; 3], roughness: f32) -> LevelMaterial {
+    LevelMaterial { color, emissive: [0.0; 3], metallic: 0.0, roughness }
+}
+
+/// Сгенерировать арену и сделать ее текущим уровнем
+pub fn load_generated_level(
+    level: &mut CurrentLevel,
+    levels: &mut Assets<LevelDefinition>,
+    seed: u64,
+) {
+    let definition = levels.add(generate_arena(seed));
+    level.set_generated(GENERATED_LEVEL, seed, definition);
+}
+
+fn genmap_command(
+    mut console_commands: EventReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut level: ResMut<CurrentLevel>,
+    mut levels: ResMut<Assets<LevelDefinition>>,
+    mut restarts: EventWriter<RestartLevel>,
+) {
+    for command in console_commands.read().filter(|command| command.name == "genmap") {
+        let seed = match command.args.first().map(|seed| seed.parse::<u64>()) {
+            Some(Ok(seed)) => seed,
+            Some(Err(_)) => {
+                console.print("Seed must be a non-negative integer");
+                continue;
+            }
+            None => rand::random(),
+        };
+        console.print(format!("Generating arena, seed {}", seed));
+        load_generated_level(&mut level, &mut levels, seed);
+        restarts.send(RestartLevel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_arena() {
+        for seed in [0, 7, 12345, u64::MAX] {
+            let first = generate_arena(seed);
+            let second = generate_arena(seed);
+            assert_eq!(first.brushes, second.brushes);
+            assert_eq!(first.pickups, second.pickups);
+            assert_eq!(first.player_start, second.player_start);
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_arenas() {
+        assert_ne!(generate_arena(1).brushes, generate_arena(2).brushes);
+    }
+
+    #[test]
+    fn jump_height_is_limited() {
+        let reach = JumpReach::new(&GameSettings::default());
+        assert!(reach.can_jump(0.0, reach.height - JUMP_HEIGHT_MARGIN - 0.05, 0.0));
+        assert!(!reach.can_jump(0.0, reach.height - JUMP_HEIGHT_MARGIN + 0.05, 0.0));
+        assert!(!reach.can_jump(0.0, PLATFORM_HEIGHTS[1], 0.0));
+    }
+
+    #[test]
+    fn jump_distance_shrinks_with_rise() {
+        let reach = JumpReach::new(&GameSettings::default());
+        // По ровному: полное время полета 2v/g
+        let flat = reach.speed * 2.0 * reach.jump_force / reach.gravity * JUMP_SAFETY;
+        assert!(reach.can_jump(0.0, 0.0, flat - 0.01));
+        assert!(!reach.can_jump(0.0, 0.0, flat + 0.01));
+        // На возвышение приземляемся раньше - тот же зазор уже не перепрыгнуть
+        assert!(!reach.can_jump(0.0, PLATFORM_HEIGHTS[0], flat - 0.01));
+        assert!(reach.can_jump(0.0, PLATFORM_HEIGHTS[0], 1.0));
+    }
+
+    #[test]
+    fn jump_down_is_at_least_as_far_as_flat() {
+        let reach = JumpReach::new(&GameSettings::default());
+        assert!(reach.can_jump(PLATFORM_HEIGHTS[2], 0.0, 4.0));
+        assert!(reach.can_jump(PLATFORM_HEIGHTS[2], PLATFORM_HEIGHTS[0], 4.0));
+    }
+}
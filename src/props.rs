@@ -0,0 +1,216 @@
+// src/props.rs - Разрушаемые предметы уровня: ящики разваливаются на обломки,
+// бочки взрываются - урон и толчок по кругу, соседние бочки детонируют цепочкой.
+// Разрушенное укрытие вырезается из навигационной сетки
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use crate::death::spawn_debris;
+use crate::enemies::{Enemy, Health, Hit};
+use crate::navigation::NavMesh;
+use crate::perception::NoiseEvent;
+use crate::player::{self, Player};
+use crate::state::{GameState, InGame};
+use crate::world::Launched;
+
+pub struct PropsPlugin;
+impl Plugin for PropsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Detonation>()
+           .add_systems(Update, (
+               break_props,
+               apply_detonations,
+               update_explosion_flashes,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Задержка между попаданием и взрывом бочки - цепочка взрывов идет волной, а не разом
+const FUSE_TIME: f32 = 0.15;
+// Сколько живет вспышка взрыва (сек)
+const FLASH_TIME: f32 = 0.35;
+// Сколько подброшенный взрывом враг не управляет собой (сек)
+const KNOCKBACK_TIME: f32 = 0.5;
+// Взрыв слышно дальше, чем выстрел
+const EXPLOSION_NOISE_MULTIPLIER: f32 = 4.0;
+
+/// Параметры взрыва: урон и толчок в центре, к краю радиуса спадают до нуля
+#[derive(Deserialize, Clone, Copy)]
+pub struct Explosion {
+    pub damage: f32,
+    pub radius: f32,
+    /// Толчок в м/с в центре взрыва
+    #[serde(default = "default_push")]
+    pub push: f32,
+}
+
+fn default_push() -> f32 {
+    8.0
+}
+
+/// Разрушаемый предмет; здоровье - обычный `Health`
+#[derive(Component)]
+pub struct Destructible {
+    /// Номер предмета в описании уровня - по нему сохранение узнает разрушенные
+    pub index: usize,
+    /// Размер предмета и цвет - по ним нарезаются обломки
+    pub size: Vec3,
+    pub color: Color,
+    pub explosion: Option<Explosion>,
+}
+
+/// Бочка подожжена и вот-вот взорвется
+#[derive(Component)]
+struct Fuse(f32);
+
+#[derive(Event)]
+pub struct Detonation {
+    pub position: Vec3,
+    pub explosion: Explosion,
+    /// Кто поджег - ему засчитываются убийства взрывом
+    pub source: Option<Entity>,
+}
+
+#[derive(Component)]
+struct ExplosionFlash {
+    timer: f32,
+    radius: f32,
+}
+
+fn break_props(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut navmesh: ResMut<NavMesh>,
+    mut props: Query<(Entity, &Transform, &Health, &Destructible, Option<&mut Fuse>)>,
+    mut detonations: EventWriter<Detonation>,
+) {
+    for (entity, transform, health, prop, fuse) in props.iter_mut() {
+        if !health.is_dead() {
+            continue;
+        }
+        let source = health.last_hit.and_then(|hit| hit.source);
+        let mut impulse = health.last_hit.map_or(Vec3::ZERO, |hit| hit.impulse);
+        if let Some(explosion) = prop.explosion {
+            match fuse {
+                None => {
+                    commands.entity(entity).insert(Fuse(FUSE_TIME));
+                    continue;
+                }
+                Some(mut fuse) if fuse.0 > 0.0 => {
+                    fuse.0 -= time.delta_secs();
+                    continue;
+                }
+                Some(_) => {}
+            }
+            detonations.send(Detonation { position: transform.translation, explosion, source });
+            impulse += Vec3::Y * explosion.push * 0.5;
+        }
+
+        spawn_debris(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            transform.translation,
+            prop.size,
+            prop.color,
+            impulse,
+            entity.index(),
+        );
+        commands.entity(entity).despawn_recursive();
+        // Предмет был статичной геометрией - сетку нужно перезапечь без него
+        navmesh.dirty = true;
+    }
+}
+
+fn apply_detonations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut detonations: EventReader<Detonation>,
+    mut targets: Query<(Entity, &Transform, &mut Health)>,
+    mut bodies: Query<(Entity, &Transform, &mut Velocity, Has<Enemy>)>,
+    mut player_query: Query<(Entity, &Transform, &mut player::Velocity), With<Player>>,
+    mut noise_events: EventWriter<NoiseEvent>,
+) {
+    for detonation in detonations.read() {
+        let Explosion { damage, radius, push } = detonation.explosion;
+        // Доля силы взрыва на расстоянии и направление толчка (немного вверх)
+        let falloff = |position: Vec3| {
+            let offset = position - detonation.position;
+            let strength = (1.0 - offset.length() / radius).max(0.0);
+            (strength, (offset.normalize_or_zero() + Vec3::Y * 0.5).normalize())
+        };
+
+        for (_, transform, mut health) in targets.iter_mut() {
+            let (strength, direction) = falloff(transform.translation);
+            if strength > 0.0 {
+                health.take_damage(damage * strength, Hit {
+                    source: detonation.source,
+                    impulse: direction * push * strength,
+                });
+            }
+        }
+        for (entity, transform, mut velocity, enemy) in bodies.iter_mut() {
+            let (strength, direction) = falloff(transform.translation);
+            if strength <= 0.0 {
+                continue;
+            }
+            velocity.linvel += direction * push * strength;
+            if enemy {
+                // Враг мог погибнуть в этом кадре и уже быть удален - тогда вставка просто пропускается
+                commands.entity(entity).try_insert(Launched { remaining: KNOCKBACK_TIME });
+            }
+        }
+        if let Ok((entity, transform, mut velocity)) = player_query.get_single_mut() {
+            let (strength, direction) = falloff(transform.translation);
+            if strength > 0.0 {
+                velocity.0 += direction * push * strength;
+                commands.entity(entity).try_insert(Launched { remaining: KNOCKBACK_TIME });
+            }
+        }
+
+        noise_events.send(NoiseEvent {
+            source: detonation.source.unwrap_or(Entity::PLACEHOLDER),
+            position: detonation.position,
+            radius: radius * EXPLOSION_NOISE_MULTIPLIER,
+        });
+
+        commands.spawn((
+            Mesh3d(meshes.add(Sphere::new(1.0))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(1.0, 0.6, 0.1, 0.8),
+                emissive: LinearRgba::rgb(8.0, 3.0, 0.5),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            Transform::from_translation(detonation.position).with_scale(Vec3::splat(radius * 0.2)),
+            ExplosionFlash { timer: FLASH_TIME, radius },
+            Name::new("Explosion"),
+            StateScoped(InGame),
+        ));
+    }
+}
+
+fn update_explosion_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut flashes: Query<(Entity, &mut ExplosionFlash, &mut Transform, &MeshMaterial3d<StandardMaterial>)>,
+) {
+    for (entity, mut flash, mut transform, material) in flashes.iter_mut() {
+        flash.timer -= time.delta_secs();
+        if flash.timer <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Шар быстро раздувается до радиуса взрыва и гаснет
+        let progress = 1.0 - flash.timer / FLASH_TIME;
+        transform.scale = Vec3::splat(flash.radius * (0.2 + 0.8 * progress.sqrt()));
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.base_color.set_alpha(0.8 * (1.0 - progress));
+            material.emissive = LinearRgba::rgb(8.0, 3.0, 0.5) * (1.0 - progress);
+        }
+    }
+}
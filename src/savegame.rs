@@ -0,0 +1,406 @@
+// src/savegame.rs - Быстрое сохранение (F5) и загрузка (F9) состояния уровня.
+// Сохраняется только состояние: враги и предметы при загрузке заново собираются
+// из своих типов (меши, коллайдеры, ИИ), поэтому вместо DynamicScene со всеми
+// компонентами пишем компактный снимок в RON с номером версии формата
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::archetypes::{ArchetypeName, EnemyArchetypes, EnemyCommandsExt};
+use crate::boss::Boss;
+use crate::enemies::{Enemy, Health};
+use crate::movers::{Mover, MoverState};
+use crate::navigation::NavMesh;
+use crate::perception::Perception;
+use crate::pickups::{Pickup, PickupCatalog, PickupCommandsExt, PickupLifetime, PickupSpawner, Pickups, PICKUP_HEIGHT};
+use crate::player::{self, spawn_player, GameSettings, Player};
+use crate::props::Destructible;
+use crate::score::{reset_score, Score};
+use crate::scripts::{resume_sequence, CvarOverrides, RunningSequence, ScriptProgress, ScriptTrigger, SequenceProgress};
+use crate::state::{GameState, InGame, RestartLevel};
+use crate::waves::{load_waves, WaveDirector, WaveMember, WaveProgress};
+use crate::weapons::{Weapon, WeaponAim};
+use crate::level::{spawn_level, CurrentLevel, LevelDefinition};
+use crate::procgen::load_generated_level;
+
+pub struct SaveGamePlugin;
+impl Plugin for SaveGamePlugin {
+    fn build(&self, app: &mut App) {
+        // Загрузка пересоздает уровень через перезапуск, а снимок накладывается
+        // сразу после того, как уровень построен
+        app.add_systems(OnEnter(InGame), apply_pending_load
+               .after(spawn_player)
+               .after(load_waves)
+               .after(reset_score)
+               .after(spawn_level)
+               .run_if(resource_exists::<PendingLoad>))
+           .add_systems(Update, (
+               quick_save.run_if(in_state(GameState::Playing)),
+               quick_load.run_if(not(in_state(GameState::Boot))),
+           ));
+    }
+}
+
+const QUICK_SAVE_FILE: &str = "quicksave.ron";
+// Увеличивать при любом несовместимом изменении формата
+const SAVE_VERSION: u32 = 3;
+// Точка спавна предмета узнается по месту: сущности между запусками другие
+const SPAWNER_MATCH_DISTANCE: f32 = 0.1;
+
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    version: u32,
+    level: String,
+    seed: Option<u64>,
+    player: PlayerState,
+    enemies: Vec<EnemyState>,
+    /// Выпавшая добыча; предметы на точках спавна хранятся в `spawners`
+    pickups: Vec<PickupState>,
+    spawners: Vec<SpawnerState>,
+    waves: WaveProgress,
+    score: Score,
+    /// Уцелевшие разрушаемые предметы; остальные при загрузке убираются
+    props: Vec<PropState>,
+    /// Скрипты уровня: какие уже сработали, и недоигранные последовательности
+    scripts: Vec<ScriptProgress>,
+    sequences: Vec<SequenceProgress>,
+    /// Двери и лифты: открытая скриптом дверь не должна закрыться после загрузки
+    movers: Vec<MoverState>,
+    /// Переменные, измененные скриптами
+    cvars: Vec<(String, f32)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct HealthState {
+    current: f32,
+    max: f32,
+    armor: f32,
+    max_armor: f32,
+}
+
+impl HealthState {
+    fn new(health: &Health) -> Self {
+        Self {
+            current: health.current,
+            max: health.max,
+            armor: health.armor,
+            max_armor: health.max_armor,
+        }
+    }
+
+    fn apply(&self, health: &mut Health) {
+        health.current = self.current;
+        health.max = self.max;
+        health.armor = self.armor;
+        health.max_armor = self.max_armor;
+        health.last_hit = None;
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlayerState {
+    translation: Vec3,
+    rotation: Quat,
+    velocity: Vec3,
+    health: HealthState,
+    weapon: Option<Weapon>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct EnemyState {
+    archetype: String,
+    translation: Vec3,
+    rotation: Quat,
+    velocity: Vec3,
+    health: HealthState,
+    wave: Option<usize>,
+    // Что враг знает об игроке
+    facing: Vec3,
+    last_known_position: Option<Vec3>,
+    time_since_sensed: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PickupState {
+    item: String,
+    translation: Vec3,
+    lifetime: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SpawnerState {
+    item: String,
+    translation: Vec3,
+    timer: f32,
+    occupied: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PropState {
+    index: usize,
+    health: HealthState,
+}
+
+/// Снимок, который надо наложить на только что построенный уровень
+#[derive(Resource)]
+struct PendingLoad(SaveGame);
+
+fn quick_save(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    level: Res<CurrentLevel>,
+    score: Res<Score>,
+    director: Option<Res<WaveDirector>>,
+    player_query: Query<(&Transform, &player::Velocity, &Health, Option<&Weapon>), With<Player>>,
+    // Бой с боссом не сохраняется: после загрузки арена снова ждет игрока
+    enemies: Query<(&Transform, &Velocity, &Health, &ArchetypeName, &Perception, Option<&WaveMember>), (With<Enemy>, Without<Boss>)>,
+    pickups: Query<(&Transform, &Pickup, &PickupLifetime)>,
+    spawners: Query<(&Transform, &PickupSpawner)>,
+    existing_pickups: Query<(), With<Pickup>>,
+    props: Query<(&Destructible, &Health)>,
+    scripts: Query<&ScriptTrigger>,
+    sequences: Query<&RunningSequence>,
+    movers: Query<&Mover>,
+    cvar_overrides: Res<CvarOverrides>,
+    settings: Res<GameSettings>,
+) {
+    if !keyboard.just_pressed(KeyCode::F5) {
+        return;
+    }
+    let (Ok((transform, velocity, health, weapon)), Some(director)) = (player_query.get_single(), director) else {
+        return;
+    };
+
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        level: level.name.clone(),
+        seed: level.seed,
+        player: PlayerState {
+            translation: transform.translation,
+            rotation: transform.rotation,
+            velocity: velocity.0,
+            health: HealthState::new(health),
+            weapon: weapon.cloned(),
+        },
+        enemies: enemies
+            .iter()
+            // Умирающие уже отыграли свое - их не восстанавливаем
+            .filter(|(_, _, health, ..)| !health.is_dead())
+            .map(|(transform, velocity, health, archetype, perception, member)| EnemyState {
+                archetype: archetype.0.clone(),
+                translation: transform.translation,
+                rotation: transform.rotation,
+                velocity: velocity.linvel,
+                health: HealthState::new(health),
+                wave: member.map(|member| member.wave),
+                facing: perception.facing,
+                last_known_position: perception.last_known_position,
+                time_since_sensed: perception.time_since_sensed,
+            })
+            .collect(),
+        pickups: pickups
+            .iter()
+            .map(|(transform, pickup, lifetime)| PickupState {
+                item: pickup.item.clone(),
+                translation: transform.translation - Vec3::Y * PICKUP_HEIGHT,
+                lifetime: lifetime.0,
+            })
+            .collect(),
+        spawners: spawners
+            .iter()
+            .map(|(transform, spawner)| {
+                let (timer, current) = spawner.state();
+                SpawnerState {
+                    item: spawner.item.clone(),
+                    translation: transform.translation,
+                    timer,
+                    occupied: current.is_some_and(|current| existing_pickups.contains(current)),
+                }
+            })
+            .collect(),
+        waves: director.progress(),
+        score: score.clone(),
+        props: props
+            .iter()
+            // Подожженная бочка при загрузке просто исчезает
+            .filter(|(_, health)| !health.is_dead())
+            .map(|(prop, health)| PropState { index: prop.index, health: HealthState::new(health) })
+            .collect(),
+        scripts: scripts.iter().map(ScriptTrigger::progress).collect(),
+        sequences: sequences.iter().map(RunningSequence::progress).collect(),
+        movers: movers.iter().map(Mover::state).collect(),
+        cvars: cvar_overrides.changed(&settings),
+    };
+
+    match write_save(Path::new(QUICK_SAVE_FILE), &save) {
+        Ok(()) => info!("Game saved to {} ({} enemies)", QUICK_SAVE_FILE, save.enemies.len()),
+        Err(error) => error!("Could not save the game: {}", error),
+    }
+}
+
+fn write_save(path: &Path, save: &SaveGame) -> Result<(), String> {
+    let text = ron::ser::to_string_pretty(save, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())?;
+    // Через временный файл, чтобы сбой посреди записи не испортил прошлое сохранение
+    let temporary = path.with_extension("ron.tmp");
+    fs::write(&temporary, text)
+        .and_then(|_| fs::rename(&temporary, path))
+        .map_err(|error| error.to_string())
+}
+
+fn read_save(path: &Path) -> Result<SaveGame, String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    // Сначала смотрим только на версию, чтобы старый формат давал понятную ошибку
+    #[derive(Deserialize)]
+    struct Header {
+        version: u32,
+    }
+    let header: Header = ron::from_str(&text).map_err(|error| error.to_string())?;
+    if header.version != SAVE_VERSION {
+        return Err(format!("save version {} is not supported (expected {})", header.version, SAVE_VERSION));
+    }
+    ron::from_str(&text).map_err(|error| error.to_string())
+}
+
+fn quick_load(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    asset_server: Res<AssetServer>,
+    mut level: ResMut<CurrentLevel>,
+    mut levels: ResMut<Assets<LevelDefinition>>,
+    mut restarts: EventWriter<RestartLevel>,
+) {
+    if !keyboard.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let save = match read_save(Path::new(QUICK_SAVE_FILE)) {
+        Ok(save) => save,
+        Err(error) => {
+            warn!("Could not load {}: {}", QUICK_SAVE_FILE, error);
+            return;
+        }
+    };
+    info!("Loading {}", QUICK_SAVE_FILE);
+    // Сгенерированную арену не нужно хранить целиком - ее восстанавливает зерно
+    match save.seed {
+        Some(seed) => load_generated_level(&mut level, &mut levels, seed),
+        None => level.load(&save.level, &asset_server),
+    }
+    commands.insert_resource(PendingLoad(save));
+    restarts.send(RestartLevel);
+}
+
+fn apply_pending_load(
+    mut commands: Commands,
+    pending: Res<PendingLoad>,
+    archetypes: Res<EnemyArchetypes>,
+    pickups: Res<Pickups>,
+    catalogs: Res<Assets<PickupCatalog>>,
+    mut score: ResMut<Score>,
+    mut director: ResMut<WaveDirector>,
+    mut player_query: Query<(Entity, &mut Transform, &mut player::Velocity, &mut Health), With<Player>>,
+    mut spawners: Query<(&Transform, &mut PickupSpawner), Without<Player>>,
+    mut props: Query<(Entity, &Destructible, &mut Health), Without<Player>>,
+    mut scripts: Query<&mut ScriptTrigger>,
+    mut movers: Query<(&mut Mover, &mut Transform), Without<Player>>,
+    mut cvar_overrides: ResMut<CvarOverrides>,
+    mut settings: ResMut<GameSettings>,
+    mut navmesh: ResMut<NavMesh>,
+) {
+    commands.remove_resource::<PendingLoad>();
+    let save = &pending.0;
+
+    if let Ok((entity, mut transform, mut velocity, mut health)) = player_query.get_single_mut() {
+        transform.translation = save.player.translation;
+        transform.rotation = save.player.rotation;
+        velocity.0 = save.player.velocity;
+        save.player.health.apply(&mut health);
+        if let Some(weapon) = &save.player.weapon {
+            commands.entity(entity).insert((weapon.clone(), WeaponAim::default()));
+        }
+    }
+
+    for enemy in &save.enemies {
+        if !archetypes.contains(&enemy.archetype) {
+            warn!("Saved enemy type '{}' no longer exists, skipped", enemy.archetype);
+            continue;
+        }
+        let state = enemy.clone();
+        let mut entity = commands.spawn_enemy_archetype(&state.archetype, state.translation);
+        if let Some(wave) = state.wave {
+            entity.insert(WaveMember { wave });
+        }
+        // Поверх компонентов, выставленных типом врага
+        entity.queue(move |mut enemy: EntityWorldMut| {
+            if let Some(mut transform) = enemy.get_mut::<Transform>() {
+                transform.rotation = state.rotation;
+            }
+            if let Some(mut velocity) = enemy.get_mut::<Velocity>() {
+                velocity.linvel = state.velocity;
+            }
+            if let Some(mut health) = enemy.get_mut::<Health>() {
+                state.health.apply(&mut health);
+            }
+            if let Some(mut perception) = enemy.get_mut::<Perception>() {
+                perception.facing = state.facing;
+                perception.last_known_position = state.last_known_position;
+                perception.time_since_sensed = state.time_since_sensed;
+            }
+        });
+    }
+
+    for pickup in &save.pickups {
+        if !pickups.contains(&catalogs, &pickup.item) {
+            warn!("Saved pickup '{}' no longer exists, skipped", pickup.item);
+            continue;
+        }
+        commands
+            .spawn_pickup(&pickup.item, pickup.translation)
+            .insert(PickupLifetime(pickup.lifetime));
+    }
+
+    for (transform, mut spawner) in spawners.iter_mut() {
+        let saved = save.spawners.iter().find(|saved| {
+            saved.item == spawner.item
+                && saved.translation.distance(transform.translation) < SPAWNER_MATCH_DISTANCE
+        });
+        let Some(saved) = saved else {
+            continue;
+        };
+        let current = saved.occupied.then(|| commands.spawn_pickup(&saved.item, transform.translation).id());
+        spawner.restore(saved.timer, current);
+    }
+
+    for (entity, prop, mut health) in props.iter_mut() {
+        match save.props.iter().find(|saved| saved.index == prop.index) {
+            Some(saved) => saved.health.apply(&mut health),
+            None => {
+                commands.entity(entity).despawn_recursive();
+                navmesh.dirty = true;
+            }
+        }
+    }
+
+    // Уже сработавшие скрипты не должны повториться поверх восстановленных врагов
+    for mut script in scripts.iter_mut() {
+        if let Some(saved) = save.scripts.iter().find(|saved| saved.index == script.index) {
+            script.restore(saved);
+        }
+    }
+    for (mut mover, mut transform) in movers.iter_mut() {
+        if let Some(saved) = save.movers.iter().find(|saved| saved.index == mover.index) {
+            mover.restore(saved, &mut transform);
+        }
+    }
+    for sequence in &save.sequences {
+        resume_sequence(&mut commands, sequence.clone());
+    }
+    for (name, value) in &save.cvars {
+        cvar_overrides.set(&mut settings, name, *value);
+    }
+
+    director.restore(save.waves.clone());
+    *score = save.score.clone();
+    info!("Loaded {} enemies and {} pickups", save.enemies.len(), save.pickups.len());
+}
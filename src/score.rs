@@ -0,0 +1,237 @@
+// src/score.rs - Очки за убийства: цена типа врага, множитель комбо,
+// бонусы за стиль (в воздухе, на скорости выше sv_maxspeed) и серии убийств
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::archetypes::{EnemyArchetype, EnemyArchetypes};
+use crate::death::EnemyKilled;
+use crate::enemies::Enemy;
+use crate::player::{self, GameSettings, Player};
+use crate::state::{spawn_game_over_screen, GameOverScreen, GameState, InGame};
+use crate::weapons::{BulletHit, WeaponFired};
+
+pub struct ScorePlugin;
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+           .add_event::<StreakAnnounced>()
+           .add_systems(OnEnter(InGame), reset_score)
+           .add_systems(OnEnter(GameState::GameOver), show_score_breakdown.after(spawn_game_over_screen))
+           .add_systems(Update, (
+               score_kills,
+               decay_combo,
+               track_run,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Комбо держится столько секунд после убийства, потом множитель тает
+const COMBO_WINDOW: f32 = 3.0;
+const COMBO_STEP: f32 = 0.25;  // Прибавка множителя за каждое убийство
+const MAX_COMBO: f32 = 4.0;
+const COMBO_DECAY: f32 = 0.5;  // Потеря множителя в секунду после окна
+// Бонусы за стиль - доля от цены врага
+const AIRBORNE_BONUS: f32 = 0.5;
+const SPEED_BONUS: f32 = 0.5;
+// Убийства с паузой не больше этой идут в одну серию
+const STREAK_WINDOW: f32 = 4.0;
+
+const STREAKS: &[(u32, &str)] = &[
+    (2, "Double Kill"),
+    (3, "Triple Kill"),
+    (5, "Rampage"),
+    (8, "Unstoppable"),
+    (12, "Godlike"),
+];
+
+/// Очки текущего забега и их разбивка для экрана конца игры
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct Score {
+    pub total: u32,
+    pub kills: u32,
+    pub combo: f32,
+    combo_timer: f32,
+    pub streak: u32,
+    streak_timer: f32,
+    pub best_combo: f32,
+    pub best_streak: u32,
+    // Из чего сложился итог
+    pub base_points: u32,
+    pub style_points: u32,
+    pub combo_points: u32,
+    pub airborne_kills: u32,
+    pub speed_kills: u32,
+    // Статистика забега
+    pub time: f32,
+    pub shots_fired: u32,
+    pub shots_hit: u32,
+}
+
+impl Score {
+    /// Доля пуль игрока, попавших во врагов
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.0;
+        }
+        (self.shots_hit as f32 / self.shots_fired as f32).min(1.0)
+    }
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            kills: 0,
+            combo: 1.0,
+            combo_timer: 0.0,
+            streak: 0,
+            streak_timer: 0.0,
+            best_combo: 1.0,
+            best_streak: 0,
+            base_points: 0,
+            style_points: 0,
+            combo_points: 0,
+            airborne_kills: 0,
+            speed_kills: 0,
+            time: 0.0,
+            shots_fired: 0,
+            shots_hit: 0,
+        }
+    }
+}
+
+/// Серия убийств дошла до очередного порога
+#[derive(Event)]
+pub struct StreakAnnounced {
+    pub name: &'static str,
+    pub streak: u32,
+}
+
+pub fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn score_kills(
+    settings: Res<GameSettings>,
+    archetypes: Res<EnemyArchetypes>,
+    archetype_assets: Res<Assets<EnemyArchetype>>,
+    players: Query<(&player::Velocity, Option<&KinematicCharacterControllerOutput>), With<Player>>,
+    mut score: ResMut<Score>,
+    mut killed: EventReader<EnemyKilled>,
+    mut streaks: EventWriter<StreakAnnounced>,
+) {
+    for event in killed.read() {
+        // Очки только за убийства игрока - не за взрывы и не за огонь по своим
+        let Some((velocity, output)) = event.killer.and_then(|killer| players.get(killer).ok()) else {
+            continue;
+        };
+        let base = event.archetype
+            .as_deref()
+            .and_then(|name| archetypes.get(name, &archetype_assets))
+            .map_or(0, |archetype| archetype.score);
+
+        let mut style = 0.0;
+        if output.is_some_and(|output| !output.grounded) {
+            style += AIRBORNE_BONUS;
+            score.airborne_kills += 1;
+        }
+        if Vec2::new(velocity.0.x, velocity.0.z).length() > settings.sv_maxspeed {
+            style += SPEED_BONUS;
+            score.speed_kills += 1;
+        }
+
+        // Стиль считается от цены врага, множитель комбо - от всего вместе
+        let styled = (base as f32 * (1.0 + style)).round() as u32;
+        let points = (styled as f32 * score.combo).round() as u32;
+        score.base_points += base;
+        score.style_points += styled - base;
+        score.combo_points += points - styled;
+        score.total += points;
+        score.kills += 1;
+
+        // Множитель растет после начисления: первое убийство идет по x1
+        score.combo = (score.combo + COMBO_STEP).min(MAX_COMBO);
+        score.combo_timer = COMBO_WINDOW;
+        score.best_combo = score.best_combo.max(score.combo);
+
+        score.streak = if score.streak_timer > 0.0 { score.streak + 1 } else { 1 };
+        score.streak_timer = STREAK_WINDOW;
+        score.best_streak = score.best_streak.max(score.streak);
+        if let Some((_, name)) = STREAKS.iter().find(|(count, _)| *count == score.streak) {
+            streaks.send(StreakAnnounced { name, streak: score.streak });
+        }
+    }
+}
+
+fn decay_combo(time: Res<Time>, mut score: ResMut<Score>) {
+    let dt = time.delta_secs();
+    if score.combo_timer > 0.0 {
+        score.combo_timer -= dt;
+    } else if score.combo > 1.0 {
+        score.combo = (score.combo - COMBO_DECAY * dt).max(1.0);
+    }
+
+    if score.streak_timer > 0.0 {
+        score.streak_timer -= dt;
+        if score.streak_timer <= 0.0 {
+            score.streak = 0;
+        }
+    }
+}
+
+fn track_run(
+    time: Res<Time>,
+    players: Query<(), With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+    mut score: ResMut<Score>,
+    mut fired: EventReader<WeaponFired>,
+    mut hits: EventReader<BulletHit>,
+) {
+    score.time += time.delta_secs();
+    for shot in fired.read() {
+        if players.contains(shot.shooter) {
+            score.shots_fired += shot.projectiles;
+        }
+    }
+    for hit in hits.read() {
+        if hit.shooter.is_some_and(|shooter| players.contains(shooter)) && enemies.contains(hit.target) {
+            score.shots_hit += 1;
+        }
+    }
+}
+
+/// Разбивка очков между заголовком и подсказкой экрана конца игры
+pub fn show_score_breakdown(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    screens: Query<Entity, With<GameOverScreen>>,
+) {
+    let Ok(screen) = screens.get_single() else {
+        return;
+    };
+    let breakdown = format!(
+        "Score: {}\n\nKills: {}   Accuracy: {:.0}%\nBase: {}\nStyle bonus: {} ({} airborne, {} at speed)\nCombo bonus: {} (best x{:.2})\nBest streak: {}",
+        score.total,
+        score.kills,
+        score.accuracy() * 100.0,
+        score.base_points,
+        score.style_points,
+        score.airborne_kills,
+        score.speed_kills,
+        score.combo_points,
+        score.best_combo,
+        score.best_streak,
+    );
+    let text = commands.spawn((
+        Text::new(breakdown),
+        TextFont {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+    )).id();
+    commands.entity(screen).insert_children(1, &[text]);
+}
@@ -0,0 +1,306 @@
+// src/scripts.rs - Скрипты уровня: условие (вход в зону, выход из нее, все враги убиты, таймер)
+// запускает действия - волна, дверь, сообщение, звук, переменная. Действия списка выполняются сразу,
+// `Sequence` - по очереди с паузами `Delay`
+use std::collections::HashMap;
+
+use bevy::audio::Volume;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::enemies::Enemy;
+use crate::movers::{ActivateMover, Mover};
+use crate::player::{GameSettings, Player};
+use crate::state::{GameState, InGame};
+use crate::ui::ShowMessage;
+use crate::waves::StartWave;
+
+pub struct ScriptsPlugin;
+impl Plugin for ScriptsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CvarOverrides>()
+           .add_systems(Update, (
+               fire_triggers,
+               run_sequences,
+           ).chain().run_if(in_state(GameState::Playing)))
+           .add_systems(OnExit(InGame), restore_cvars);
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub enum ScriptCondition {
+    /// Игрок вошел в зону
+    Enter { position: [f32; 3], half_extents: [f32; 3] },
+    /// Игрок вышел из зоны
+    Exit { position: [f32; 3], half_extents: [f32; 3] },
+    /// На уровне были враги, и их не осталось
+    AllEnemiesDead,
+    /// Через `delay` секунд после начала уровня (и каждые `delay` секунд, если не `once`)
+    Timer { delay: f32 },
+}
+
+impl ScriptCondition {
+    /// Зона условия: центр и половины размеров
+    pub fn volume(&self) -> Option<([f32; 3], [f32; 3])> {
+        match *self {
+            ScriptCondition::Enter { position, half_extents } | ScriptCondition::Exit { position, half_extents } => {
+                Some((position, half_extents))
+            }
+            ScriptCondition::AllEnemiesDead | ScriptCondition::Timer { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum ScriptAction {
+    /// Волна из набора волн уровня, по имени
+    SpawnWave(String),
+    /// Запустить подвижное тело по имени - дверь, лифт
+    OpenDoor(String),
+    Message {
+        text: String,
+        #[serde(default = "default_message_duration")]
+        duration: f32,
+    },
+    /// Путь к звуку в assets
+    Sound {
+        path: String,
+        #[serde(default = "default_volume")]
+        volume: f32,
+    },
+    /// Переменная `GameSettings` (sv_gravity и т.п.); с концом уровня возвращается прежнее значение
+    SetCvar { name: String, value: f32 },
+    /// Пауза внутри `Sequence`; в общем списке действий ничего не делает
+    Delay(f32),
+    /// Действия по очереди. Вложенная `Sequence` выполняется целиком, прежде чем идти дальше
+    Sequence(Vec<ScriptAction>),
+}
+
+fn default_message_duration() -> f32 {
+    3.0
+}
+
+fn default_volume() -> f32 {
+    1.0
+}
+
+#[derive(Component)]
+pub struct ScriptTrigger {
+    /// Номер скрипта в описании уровня - по нему восстанавливается сохранение
+    pub index: usize,
+    condition: ScriptCondition,
+    actions: Vec<ScriptAction>,
+    once: bool,
+    fired: bool,
+    /// Игрок в зоне или враги на уровне в прошлом кадре - срабатываем на смене
+    active: bool,
+    elapsed: f32,
+}
+
+impl ScriptTrigger {
+    pub fn new(index: usize, condition: ScriptCondition, actions: Vec<ScriptAction>, once: bool) -> Self {
+        Self { index, condition, actions, once, fired: false, active: false, elapsed: 0.0 }
+    }
+
+    pub fn progress(&self) -> ScriptProgress {
+        ScriptProgress { index: self.index, fired: self.fired, active: self.active, elapsed: self.elapsed }
+    }
+
+    pub fn restore(&mut self, progress: &ScriptProgress) {
+        self.fired = progress.fired;
+        self.active = progress.active;
+        self.elapsed = progress.elapsed;
+    }
+}
+
+/// Состояние скрипта для сохранения игры
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ScriptProgress {
+    pub index: usize,
+    fired: bool,
+    active: bool,
+    elapsed: f32,
+}
+
+/// Выполняющаяся `Sequence`
+#[derive(Component)]
+pub struct RunningSequence {
+    steps: Vec<ScriptAction>,
+    next: usize,
+    wait: f32,
+}
+
+/// Недоигранная `Sequence` для сохранения игры: оставшиеся шаги и пауза
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SequenceProgress {
+    steps: Vec<ScriptAction>,
+    wait: f32,
+}
+
+impl RunningSequence {
+    pub fn progress(&self) -> SequenceProgress {
+        SequenceProgress { steps: self.steps[self.next..].to_vec(), wait: self.wait }
+    }
+}
+
+/// Запустить `Sequence` с места, описанного `progress`
+pub fn resume_sequence(commands: &mut Commands, progress: SequenceProgress) {
+    commands.spawn((
+        RunningSequence { steps: progress.steps, next: 0, wait: progress.wait },
+        Name::new("Script Sequence"),
+        StateScoped(InGame),
+    ));
+}
+
+/// Значения переменных до того, как их поменяли скрипты
+#[derive(Resource, Default)]
+pub struct CvarOverrides(HashMap<String, f32>);
+
+impl CvarOverrides {
+    /// Меняет переменную, запомнив исходное значение. false - такой переменной нет
+    pub fn set(&mut self, settings: &mut GameSettings, name: &str, value: f32) -> bool {
+        let Some(cvar) = settings.cvar_mut(name) else {
+            return false;
+        };
+        self.0.entry(name.to_string()).or_insert(*cvar);
+        *cvar = value;
+        true
+    }
+
+    /// Измененные скриптами переменные и их текущие значения
+    pub fn changed(&self, settings: &GameSettings) -> Vec<(String, f32)> {
+        self.0
+            .keys()
+            .filter_map(|name| settings.cvar(name).map(|value| (name.clone(), value)))
+            .collect()
+    }
+}
+
+#[derive(SystemParam)]
+struct ScriptActions<'w, 's> {
+    commands: Commands<'w, 's>,
+    asset_server: Res<'w, AssetServer>,
+    settings: ResMut<'w, GameSettings>,
+    overrides: ResMut<'w, CvarOverrides>,
+    movers: Query<'w, 's, (Entity, &'static Name), With<Mover>>,
+    waves: EventWriter<'w, StartWave>,
+    activations: EventWriter<'w, ActivateMover>,
+    messages: EventWriter<'w, ShowMessage>,
+}
+
+impl ScriptActions<'_, '_> {
+    fn run(&mut self, action: &ScriptAction) {
+        match action {
+            ScriptAction::SpawnWave(name) => {
+                self.waves.send(StartWave { name: name.clone() });
+            }
+            ScriptAction::OpenDoor(name) => {
+                match self.movers.iter().find(|(_, mover)| mover.as_str() == name) {
+                    Some((entity, _)) => {
+                        self.activations.send(ActivateMover(entity));
+                    }
+                    None => warn!("Script: unknown mover '{}'", name),
+                }
+            }
+            ScriptAction::Message { text, duration } => {
+                self.messages.send(ShowMessage { text: text.clone(), duration: *duration });
+            }
+            ScriptAction::Sound { path, volume } => {
+                self.commands.spawn((
+                    AudioPlayer::new(self.asset_server.load(path)),
+                    PlaybackSettings::DESPAWN.with_volume(Volume::new(*volume)),
+                    Name::new("Script Sound"),
+                    StateScoped(InGame),
+                ));
+            }
+            ScriptAction::SetCvar { name, value } => {
+                if !self.overrides.set(&mut self.settings, name, *value) {
+                    warn!("Script: unknown cvar '{}'", name);
+                }
+            }
+            ScriptAction::Delay(_) => {}
+            ScriptAction::Sequence(steps) => {
+                resume_sequence(&mut self.commands, SequenceProgress { steps: steps.clone(), wait: 0.0 });
+            }
+        }
+    }
+}
+
+fn fire_triggers(
+    time: Res<Time>,
+    rapier_context: ReadRapierContext,
+    player_query: Query<Entity, With<Player>>,
+    enemies: Query<(), With<Enemy>>,
+    mut triggers: Query<(Entity, &Name, &mut ScriptTrigger)>,
+    mut actions: ScriptActions,
+) {
+    let context = rapier_context.single();
+    let player = player_query.get_single().ok();
+    let enemies_alive = !enemies.is_empty();
+    for (entity, name, mut trigger) in triggers.iter_mut() {
+        if trigger.once && trigger.fired {
+            continue;
+        }
+        let fire = match trigger.condition {
+            ScriptCondition::Enter { .. } | ScriptCondition::Exit { .. } => {
+                let inside = player.is_some_and(|player| context.intersection_pair(entity, player) == Some(true));
+                let was_inside = std::mem::replace(&mut trigger.active, inside);
+                match trigger.condition {
+                    ScriptCondition::Enter { .. } => inside && !was_inside,
+                    _ => was_inside && !inside,
+                }
+            }
+            ScriptCondition::AllEnemiesDead => std::mem::replace(&mut trigger.active, enemies_alive) && !enemies_alive,
+            ScriptCondition::Timer { delay } => {
+                trigger.elapsed += time.delta_secs();
+                let due = trigger.elapsed >= delay;
+                if due {
+                    trigger.elapsed = 0.0;
+                }
+                due
+            }
+        };
+        if !fire {
+            continue;
+        }
+        info!("Script '{}' fired", name);
+        trigger.fired = true;
+        for action in &trigger.actions {
+            actions.run(action);
+        }
+    }
+}
+
+fn run_sequences(
+    time: Res<Time>,
+    mut sequences: Query<(Entity, &mut RunningSequence)>,
+    mut actions: ScriptActions,
+) {
+    for (entity, mut sequence) in sequences.iter_mut() {
+        sequence.wait -= time.delta_secs();
+        while sequence.wait <= 0.0 {
+            let Some(step) = sequence.steps.get(sequence.next).cloned() else {
+                actions.commands.entity(entity).despawn();
+                break;
+            };
+            sequence.next += 1;
+            match step {
+                ScriptAction::Delay(delay) => sequence.wait += delay,
+                // Вложенные шаги встают на место текущего
+                ScriptAction::Sequence(inner) => {
+                    let next = sequence.next;
+                    sequence.steps.splice(next..next, inner);
+                }
+                step => actions.run(&step),
+            }
+        }
+    }
+}
+
+fn restore_cvars(mut overrides: ResMut<CvarOverrides>, mut settings: ResMut<GameSettings>) {
+    for (name, value) in overrides.0.drain() {
+        if let Some(cvar) = settings.cvar_mut(&name) {
+            *cvar = value;
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// src/state.rs - Состояния игры: загрузка, главное меню, игра, пауза, конец игры.
+// Уровень живет, пока активно `InGame`: все его сущности помечены `StateScoped(InGame)`
+// и удаляются при выходе в меню или перезапуске
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use crate::archetypes::EnemyArchetypes;
+use crate::enemies::Health;
+use crate::level::{CurrentLevel, LevelDefinition};
+use crate::pickups::{PickupCatalog, Pickups};
+use crate::player::Player;
+
+pub struct GameStatePlugin;
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        // Состояния требуют StatesPlugin - плагин добавляется после DefaultPlugins
+        app.init_state::<GameState>()
+           .add_computed_state::<InGame>()
+           .enable_state_scoped_entities::<GameState>()
+           .enable_state_scoped_entities::<InGame>()
+           .init_resource::<AfterBoot>()
+           .add_event::<RestartLevel>()
+           .add_systems(Update, finish_boot.run_if(in_state(GameState::Boot)))
+           .add_systems(OnEnter(GameState::Playing), resume_simulation)
+           .add_systems(OnExit(GameState::Playing), pause_simulation)
+           .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+           .add_systems(Update, (
+               detect_player_death.run_if(in_state(GameState::Playing)),
+               pause_input.run_if(in_state(GameState::Playing)),
+               game_over_input.run_if(in_state(GameState::GameOver)).in_set(GameOverInput),
+               restart_level,
+           ).chain());
+    }
+}
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum GameState {
+    /// Ждем загрузки ассетов, без которых нельзя начать уровень
+    #[default]
+    Boot,
+    MainMenu,
+    Playing,
+    Paused,
+    GameOver,
+}
+
+/// Уровень загружен: игра, пауза и экран конца игры поверх замершего уровня
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct InGame;
+
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
+
+    fn compute(sources: GameState) -> Option<Self> {
+        matches!(sources, GameState::Playing | GameState::Paused | GameState::GameOver).then_some(InGame)
+    }
+}
+
+/// Клавиши экрана конца игры. Другие модули могут временно отключить их
+/// (например, пока игрок вводит имя для таблицы рекордов)
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GameOverInput;
+
+/// Корень экрана конца игры - другие модули добавляют в него свои строки
+#[derive(Component)]
+pub struct GameOverScreen;
+
+/// Перезапуск уровня из меню паузы или с экрана конца игры
+#[derive(Event)]
+pub struct RestartLevel;
+
+/// Куда перейти после загрузки: при первом запуске в меню, при перезапуске сразу в игру.
+/// Перезапуск идет через `Boot`, чтобы `InGame` закончилось и уровень пересоздался
+#[derive(Resource)]
+struct AfterBoot(GameState);
+
+impl Default for AfterBoot {
+    fn default() -> Self {
+        Self(GameState::MainMenu)
+    }
+}
+
+fn finish_boot(
+    archetypes: Option<Res<EnemyArchetypes>>,
+    pickups: Option<Res<Pickups>>,
+    catalogs: Res<Assets<PickupCatalog>>,
+    level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
+    mut after_boot: ResMut<AfterBoot>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let ready = archetypes.is_some_and(|archetypes| archetypes.is_ready())
+        && pickups.is_some_and(|pickups| pickups.is_ready(&catalogs))
+        && level.is_ready(&levels);
+    if !ready {
+        return;
+    }
+    next_state.set(std::mem::replace(&mut after_boot.0, GameState::MainMenu));
+}
+
+fn restart_level(
+    mut restarts: EventReader<RestartLevel>,
+    mut after_boot: ResMut<AfterBoot>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if restarts.read().count() == 0 {
+        return;
+    }
+    info!("Restarting level");
+    after_boot.0 = GameState::Playing;
+    next_state.set(GameState::Boot);
+}
+
+/// Вне игры стоят и виртуальное время (таймеры, FixedUpdate), и физика
+fn pause_simulation(
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: Query<&mut RapierConfiguration>,
+) {
+    time.pause();
+    for mut config in physics.iter_mut() {
+        config.physics_pipeline_active = false;
+    }
+}
+
+fn resume_simulation(
+    mut time: ResMut<Time<Virtual>>,
+    mut physics: Query<&mut RapierConfiguration>,
+) {
+    time.unpause();
+    for mut config in physics.iter_mut() {
+        config.physics_pipeline_active = true;
+    }
+}
+
+fn detect_player_death(
+    player_query: Query<&Health, With<Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if player_query.get_single().is_ok_and(Health::is_dead) {
+        info!("Game Over");
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// Escape или Start на геймпаде ставят игру на паузу, дальше ею управляет меню (menu.rs)
+fn pause_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let start = gamepads.iter().any(|gamepad| gamepad.just_pressed(GamepadButton::Start));
+    if keyboard.just_pressed(KeyCode::Escape) || start {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn game_over_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut restarts: EventWriter<RestartLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let pressed = |key: KeyCode, button: GamepadButton| {
+        keyboard.just_pressed(key) || gamepads.iter().any(|gamepad| gamepad.just_pressed(button))
+    };
+    if pressed(KeyCode::KeyR, GamepadButton::South) || keyboard.just_pressed(KeyCode::Enter) {
+        restarts.send(RestartLevel);
+    } else if pressed(KeyCode::Escape, GamepadButton::East) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+/// Затемненный экран с заголовком и подсказкой поверх замершего уровня
+pub fn spawn_game_over_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            StateScoped(GameState::GameOver),
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("GAME OVER"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent.spawn((
+                Text::new("R - restart    Esc - main menu"),
+                TextFont {
+                    font,
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        });
+}
@@ -0,0 +1,255 @@
+// src/stats.rs - Модификаторы характеристик: усиления, фазы босса, сложность.
+// Базовые значения остаются в своих компонентах (`Weapon::damage`, `Enemy::speed`,
+// `GameSettings::sv_maxspeed`), а системы читают итоговое значение через `Stats::apply`
+use bevy::prelude::*;
+use serde::Deserialize;
+use crate::enemies::{Enemy, Health};
+use crate::pickups::{PickupCollected, PickupKind};
+use crate::powerups::PowerUpKind;
+use crate::state::GameState;
+
+pub struct StatsPlugin;
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+           .add_systems(Update, (
+               cycle_difficulty,
+               apply_difficulty,
+               apply_upgrade_pickups,
+               tick_modifiers,
+               regenerate_health,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Stat {
+    MoveSpeed,
+    Damage,
+    FireRate,
+    HealthRegen, // Здоровья в секунду, база 0
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum ModifierOp {
+    Add(f32),
+    Multiply(f32),
+}
+
+/// Откуда взялся модификатор - по источнику их продлевают, заменяют и снимают
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModifierSource {
+    PowerUp(PowerUpKind),
+    BossPhase,
+    Difficulty,
+    /// Зона замедления на уровне, держится, пока в ней стоят
+    Hazard,
+    /// Подобранное улучшение - действует до конца уровня
+    Upgrade,
+}
+
+/// Что делать, если модификатор той же характеристики от того же источника уже есть
+#[derive(Clone, Copy, Debug)]
+pub enum Stacking {
+    /// Старый снимается, новый занимает его место
+    Replace,
+    /// Время действия складывается, но не больше `max_duration`
+    Extend { max_duration: f32 },
+    /// Новый добавляется к старым - улучшения копятся
+    Stack,
+}
+
+#[derive(Clone, Debug)]
+pub struct StatModifier {
+    pub stat: Stat,
+    pub op: ModifierOp,
+    pub source: ModifierSource,
+    /// Сколько еще действует (сек), `None` - пока его не снимут
+    pub remaining: Option<f32>,
+}
+
+impl StatModifier {
+    pub fn new(stat: Stat, op: ModifierOp, source: ModifierSource) -> Self {
+        Self { stat, op, source, remaining: None }
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self {
+        self.remaining = Some(duration);
+        self
+    }
+}
+
+/// Действующие на сущность модификаторы
+#[derive(Component, Default)]
+pub struct Stats {
+    modifiers: Vec<StatModifier>,
+}
+
+impl Stats {
+    pub fn add(&mut self, modifier: StatModifier, stacking: Stacking) {
+        let existing = self.modifiers
+            .iter()
+            .position(|other| other.stat == modifier.stat && other.source == modifier.source);
+        match (existing, stacking) {
+            (Some(index), Stacking::Replace) => self.modifiers[index] = modifier,
+            (Some(index), Stacking::Extend { max_duration }) => {
+                let current = &mut self.modifiers[index];
+                current.op = modifier.op;
+                current.remaining = match (current.remaining, modifier.remaining) {
+                    (Some(current), Some(added)) => Some((current + added).min(max_duration)),
+                    _ => None,
+                };
+            }
+            (None, Stacking::Replace) | (_, Stacking::Stack) => self.modifiers.push(modifier),
+            (None, Stacking::Extend { max_duration }) => self.modifiers.push(StatModifier {
+                remaining: modifier.remaining.map(|remaining| remaining.min(max_duration)),
+                ..modifier
+            }),
+        }
+    }
+
+    pub fn remove_source(&mut self, source: ModifierSource) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+    }
+
+    /// Итоговое значение: сначала прибавки, затем множители
+    pub fn apply(&self, stat: Stat, base: f32) -> f32 {
+        let mut add = 0.0;
+        let mut multiply = 1.0;
+        for modifier in self.modifiers.iter().filter(|modifier| modifier.stat == stat) {
+            match modifier.op {
+                ModifierOp::Add(value) => add += value,
+                ModifierOp::Multiply(value) => multiply *= value,
+            }
+        }
+        (base + add) * multiply
+    }
+
+    /// Источники действующих модификаторов, без повторов
+    pub fn sources(&self) -> Vec<ModifierSource> {
+        let mut sources: Vec<ModifierSource> = Vec::new();
+        for modifier in &self.modifiers {
+            if !sources.contains(&modifier.source) {
+                sources.push(modifier.source);
+            }
+        }
+        sources
+    }
+
+    /// Сколько еще действует источник - по самому долгому его модификатору
+    pub fn remaining(&self, source: ModifierSource) -> Option<f32> {
+        self.modifiers
+            .iter()
+            .filter(|modifier| modifier.source == source)
+            .filter_map(|modifier| modifier.remaining)
+            .reduce(f32::max)
+    }
+}
+
+/// Сложность игры - модификаторы для всех врагов
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    fn enemy_modifiers(&self) -> [StatModifier; 2] {
+        let (speed, damage) = match self {
+            Difficulty::Easy => (0.85, 0.6),
+            Difficulty::Normal => (1.0, 1.0),
+            Difficulty::Hard => (1.15, 1.5),
+        };
+        [
+            StatModifier::new(Stat::MoveSpeed, ModifierOp::Multiply(speed), ModifierSource::Difficulty),
+            StatModifier::new(Stat::Damage, ModifierOp::Multiply(damage), ModifierSource::Difficulty),
+        ]
+    }
+}
+
+/// F4 переключает сложность прямо в игре
+fn cycle_difficulty(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut difficulty: ResMut<Difficulty>,
+) {
+    if !keyboard.just_pressed(KeyCode::F4) {
+        return;
+    }
+    *difficulty = match *difficulty {
+        Difficulty::Easy => Difficulty::Normal,
+        Difficulty::Normal => Difficulty::Hard,
+        Difficulty::Hard => Difficulty::Easy,
+    };
+    info!("Difficulty: {:?}", *difficulty);
+}
+
+/// Новые враги получают модификаторы сложности, а при ее смене они переписываются у всех
+fn apply_difficulty(
+    difficulty: Res<Difficulty>,
+    mut enemies: Query<&mut Stats, With<Enemy>>,
+) {
+    for mut stats in enemies.iter_mut() {
+        if !difficulty.is_changed() && !stats.is_added() {
+            continue;
+        }
+        stats.remove_source(ModifierSource::Difficulty);
+        for modifier in difficulty.enemy_modifiers() {
+            stats.add(modifier, Stacking::Replace);
+        }
+    }
+}
+
+/// Улучшение с арены навсегда меняет характеристику подобравшего
+fn apply_upgrade_pickups(
+    mut collected: EventReader<PickupCollected>,
+    mut holders: Query<&mut Stats>,
+) {
+    for event in collected.read() {
+        let PickupKind::Upgrade { stat, op } = event.kind else {
+            continue;
+        };
+        let Ok(mut stats) = holders.get_mut(event.collector) else {
+            continue;
+        };
+        info!("Upgrade: {:?} {:?}", stat, op);
+        stats.add(StatModifier::new(stat, op, ModifierSource::Upgrade), Stacking::Stack);
+    }
+}
+
+fn tick_modifiers(
+    time: Res<Time>,
+    mut holders: Query<&mut Stats>,
+) {
+    let dt = time.delta_secs();
+    for mut stats in holders.iter_mut() {
+        if stats.modifiers.iter().all(|modifier| modifier.remaining.is_none()) {
+            continue;
+        }
+        let sources_before = stats.sources();
+        for modifier in stats.modifiers.iter_mut() {
+            if let Some(remaining) = modifier.remaining.as_mut() {
+                *remaining -= dt;
+            }
+        }
+        stats.modifiers.retain(|modifier| modifier.remaining.is_none_or(|remaining| remaining > 0.0));
+
+        let sources_after = stats.sources();
+        for source in sources_before.iter().filter(|source| !sources_after.contains(source)) {
+            debug!("{:?} wore off", source);
+        }
+    }
+}
+
+fn regenerate_health(
+    time: Res<Time>,
+    mut holders: Query<(&Stats, &mut Health)>,
+) {
+    for (stats, mut health) in holders.iter_mut() {
+        let regen = stats.apply(Stat::HealthRegen, 0.0);
+        if regen > 0.0 && !health.is_dead() {
+            health.heal(regen * time.delta_secs());
+        }
+    }
+}
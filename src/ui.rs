@@ -1,44 +1,102 @@
 // src/ui.rs - UI без crosshair (так как теперь есть курсор на земле)
 use bevy::prelude::*;
-use bevy_rapier3d::prelude::CollisionEvent;
 use crate::player::Player;
 use crate::enemies::{Enemy, Health};
+use crate::boss::Boss;
+use crate::weapons::Weapon;
+use crate::powerups::active_power_ups;
+use crate::stats::Stats;
+use crate::state::InGame;
+use crate::score::{Score, StreakAnnounced};
+use crate::level::CurrentLevel;
 
 pub struct UIPlugin;
 impl Plugin for UIPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (setup_ui, setup_player_health))
+        app.add_event::<ShowMessage>()
+           .add_systems(OnEnter(InGame), setup_ui)
            .add_systems(Update, (
                update_health_bar,
                update_enemy_counter,
-               player_enemy_collision,
+               update_player_status,
+               update_power_up_timers,
+               update_boss_bar,
+               update_score,
+               show_streak_announcements,
+               show_messages,
            ));
     }
 }
 
-#[derrive(Component)]
+#[derive(Component)]
 struct HealthBar;
 
 #[derive(Component)]
 struct EnemyCounter;
 
-fn setup_ui(mut commands: Commands) {
+/// Броня, оружие и патроны игрока
+#[derive(Component)]
+struct PlayerStatus;
+
+/// Оставшееся время усилений, по строке на усиление
+#[derive(Component)]
+struct PowerUpTimers;
+
+/// Полоска здоровья босса вверху экрана, видна только во время боя
+#[derive(Component)]
+struct BossBar;
+
+#[derive(Component)]
+struct BossBarFill;
+
+#[derive(Component)]
+struct BossBarName;
+
+/// Очки и текущий множитель комбо
+#[derive(Component)]
+struct ScoreText;
+
+/// Объявление серии убийств, гаснет за `STREAK_BANNER_TIME`
+#[derive(Component)]
+struct StreakBanner {
+    timer: f32,
+}
+
+const STREAK_BANNER_TIME: f32 = 2.0;
+
+/// Сообщение по центру экрана, например из скрипта уровня
+#[derive(Event)]
+pub struct ShowMessage {
+    pub text: String,
+    /// Сколько держится на экране (сек), последнюю секунду гаснет
+    pub duration: f32,
+}
+
+#[derive(Component)]
+struct MessageBanner {
+    timer: f32,
+}
+
+fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>, level: Res<CurrentLevel>) {
+    // Встроенный шрифт не содержит кириллицы
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+
     // UI Root
     commands
-        .spawn(NodeBundle {
-            style: Style {
+        .spawn((
+            Node {
                 width: Val::Percent(100.0),
                 height: Val::Percent(100.0),
                 position_type: PositionType::Absolute,
                 ..default()
             },
-            ..default()
-        })
+            StateScoped(InGame),
+        ))
         .with_children(|parent| {
             // Health Bar Background
             parent
-                .spawn(NodeBundle {
-                    style: Style {
+                .spawn((
+                    Node {
                         position_type: PositionType::Absolute,
                         left: Val::Px(20.0),
                         top: Val::Px(20.0),
@@ -46,80 +104,220 @@ fn setup_ui(mut commands: Commands) {
                         height: Val::Px(20.0),
                         ..default()
                     },
-                    background_color: BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
-                    ..default()
-                })
+                    BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+                ))
                 .with_children(|parent| {
                     // Health Bar Fill
                     parent.spawn((
-                        NodeBundle {
-                            style: Style {
-                                width: Val::Percent(100.0),
-                                height: Val::Percent(100.0),
-                                ..default()
-                            },
-                            background_color: BackgroundColor(Color::srgb(0.8, 0.2, 0.2)),
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
                             ..default()
                         },
+                        BackgroundColor(Color::srgb(0.8, 0.2, 0.2)),
                         HealthBar,
                     ));
                 });
-            
+
             // Enemy Counter
             parent.spawn((
-                TextBundle::from_section(
-                    "Enemies: 0",
-                    TextStyle {
-                        font_size: 24.0,
-                        color: Color::WHITE,
-                        ..default()
-                    },
-                ).with_style(Style {
+                Text::new("Enemies: 0"),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
                     position_type: PositionType::Absolute,
                     left: Val::Px(20.0),
                     top: Val::Px(50.0),
                     ..default()
-                }),
+                },
                 EnemyCounter,
             ));
-            
-            // Инструкции управления
-            parent.spawn(
-                TextBundle::from_section(
-                    "WASD - движение, Space - прыжок, ЛКМ - стрельба",
-                    TextStyle {
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.0),
+                    top: Val::Px(80.0),
+                    ..default()
+                },
+                PlayerStatus,
+            ));
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.3)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(20.0),
+                    top: Val::Px(110.0),
+                    ..default()
+                },
+                ScoreText,
+            ));
+
+            // Зерно сгенерированной арены - чтобы ее можно было повторить командой genmap
+            if let Some(seed) = level.seed {
+                parent.spawn((
+                    Text::new(format!("Seed: {}", seed)),
+                    TextFont {
+                        font: font.clone(),
                         font_size: 16.0,
-                        color: Color::srgba(1.0, 1.0, 1.0, 0.7),
                         ..default()
                     },
-                ).with_style(Style {
+                    TextColor(Color::srgba(1.0, 1.0, 1.0, 0.6)),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(20.0),
+                        bottom: Val::Px(20.0),
+                        ..default()
+                    },
+                ));
+            }
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::NONE),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(25.0),
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                StreakBanner { timer: 0.0 },
+            ));
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(Color::NONE),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(35.0),
+                    width: Val::Percent(100.0),
+                    ..default()
+                },
+                MessageBanner { timer: 0.0 },
+            ));
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Right),
+                Node {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(20.0),
+                    top: Val::Px(20.0),
+                    ..default()
+                },
+                PowerUpTimers,
+            ));
+
+            // Полоска босса: имя над полоской по центру экрана
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(20.0),
+                        left: Val::Percent(25.0),
+                        width: Val::Percent(50.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    Visibility::Hidden,
+                    BossBar,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: 22.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        BossBarName,
+                    ));
+                    parent
+                        .spawn((
+                            Node {
+                                width: Val::Percent(100.0),
+                                height: Val::Px(16.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.15, 0.1, 0.15)),
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Node {
+                                    width: Val::Percent(100.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgb(0.6, 0.1, 0.7)),
+                                BossBarFill,
+                            ));
+                        });
+                });
+
+            // Инструкции управления
+            parent.spawn((
+                Text::new("WASD - движение, Space - прыжок, ЛКМ - стрельба"),
+                TextFont {
+                    font,
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(1.0, 1.0, 1.0, 0.7)),
+                Node {
                     position_type: PositionType::Absolute,
                     right: Val::Px(20.0),
                     bottom: Val::Px(20.0),
                     ..default()
-                }),
-            );
+                },
+            ));
         });
 }
 
-fn setup_player_health(
-    mut commands: Commands,
-    player_query: Query<Entity, (With<Player>, Without<Health>)>,
-) {
-    for player_entity in player_query.iter() {
-        commands.entity(player_entity).insert(Health::new(100.0));
-    }
-}
-
 fn update_health_bar(
     player_query: Query<&Health, With<Player>>,
-    mut health_bar_query: Query<&mut Style, With<HealthBar>>,
+    mut health_bar_query: Query<&mut Node, With<HealthBar>>,
 ) {
-    if let Ok(health) = player_query.get_single() {
-        if let Ok(mut style) = health_bar_query.get_single_mut() {
-            let health_percentage = health.current / health.max;
-            style.width = Val::Percent(health_percentage * 100.0);
-        }
+    if let (Ok(health), Ok(mut node)) = (player_query.get_single(), health_bar_query.get_single_mut()) {
+        node.width = Val::Percent(health.fraction() * 100.0);
     }
 }
 
@@ -128,41 +326,108 @@ fn update_enemy_counter(
     mut counter_query: Query<&mut Text, With<EnemyCounter>>,
 ) {
     let enemy_count = enemy_query.iter().count();
-    
+
     if let Ok(mut text) = counter_query.get_single_mut() {
-        text.sections[0].value = format!("Enemies: {}", enemy_count);
+        text.0 = format!("Enemies: {}", enemy_count);
     }
 }
 
-fn player_enemy_collision(
-    mut collision_events: EventReader<CollisionEvent>,
-    player_query: Query<Entity, With<Player>>,
-    mut player_health_query: Query<&mut Health, With<Player>>,
-    enemy_query: Query<Entity, With<Enemy>>,
+fn update_player_status(
+    player_query: Query<(&Health, Option<&Weapon>), With<Player>>,
+    mut status_query: Query<&mut Text, With<PlayerStatus>>,
 ) {
-    let Ok(player_entity) = player_query.get_single() else {
+    let (Ok((health, weapon)), Ok(mut text)) = (player_query.get_single(), status_query.get_single_mut()) else {
         return;
     };
-    
-    for collision_event in collision_events.read() {
-        if let CollisionEvent::Stopped(entity1, entity2, _) = collision_event {
-            let enemy_entity = if *entity1 == player_entity && enemy_query.contains(*entity2) {
-                Some(*entity2)
-            } else if *entity2 == player_entity && enemy_query.contains(*entity1) {
-                Some(*entity1)
-            } else {
-                None
-            };
-            
-            if enemy_entity.is_some() {
-                if let Ok(mut health) = player_health_query.get_single_mut() {
-                    health.take_damage(10.0);
-                    
-                    if health.is_dead() {
-                        println!("Game Over! Используй стрейф-джампинг для уклонения!");
-                    }
-                }
-            }
-        }
+    let ammo = match weapon {
+        Some(Weapon { name, ammo: Some(ammo), max_ammo, .. }) => format!("{}: {}/{}", name, ammo, max_ammo),
+        Some(weapon) => format!("{}: ∞", weapon.name),
+        None => String::new(),
+    };
+    text.0 = format!("Armor: {:.0}   {}", health.armor, ammo);
+}
+
+fn update_power_up_timers(
+    player_query: Query<&Stats, With<Player>>,
+    mut timers_query: Query<&mut Text, With<PowerUpTimers>>,
+) {
+    let Ok(mut text) = timers_query.get_single_mut() else {
+        return;
+    };
+    text.0 = player_query.get_single().map_or(String::new(), |stats| {
+        active_power_ups(stats)
+            .iter()
+            .map(|(kind, remaining)| format!("{} {:.0}", kind.label(), remaining.ceil()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    });
+}
+
+fn update_boss_bar(
+    boss_query: Query<(&Boss, &Health)>,
+    mut bar_query: Query<&mut Visibility, With<BossBar>>,
+    mut fill_query: Query<&mut Node, With<BossBarFill>>,
+    mut name_query: Query<&mut Text, With<BossBarName>>,
+) {
+    let Ok(mut visibility) = bar_query.get_single_mut() else {
+        return;
+    };
+    let Some((boss, health)) = boss_query.iter().next() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    if let Ok(mut fill) = fill_query.get_single_mut() {
+        fill.width = Val::Percent(health.fraction() * 100.0);
+    }
+    if let Ok(mut name) = name_query.get_single_mut() {
+        name.0 = format!("{} - фаза {}", boss.name, boss.phase + 1);
+    }
+}
+
+fn update_score(
+    score: Res<Score>,
+    mut score_query: Query<&mut Text, With<ScoreText>>,
+) {
+    let Ok(mut text) = score_query.get_single_mut() else {
+        return;
+    };
+    text.0 = if score.combo > 1.0 {
+        format!("Score: {}   x{:.2}", score.total, score.combo)
+    } else {
+        format!("Score: {}", score.total)
+    };
+}
+
+fn show_streak_announcements(
+    time: Res<Time>,
+    mut streaks: EventReader<StreakAnnounced>,
+    mut banner_query: Query<(&mut Text, &mut TextColor, &mut StreakBanner)>,
+) {
+    let Ok((mut text, mut color, mut banner)) = banner_query.get_single_mut() else {
+        return;
+    };
+    if let Some(streak) = streaks.read().last() {
+        text.0 = format!("{}!  ({} kills)", streak.name, streak.streak);
+        banner.timer = STREAK_BANNER_TIME;
+    }
+    banner.timer = (banner.timer - time.delta_secs()).max(0.0);
+    color.0 = Color::srgba(1.0, 0.3, 0.1, banner.timer / STREAK_BANNER_TIME);
+}
+
+fn show_messages(
+    time: Res<Time>,
+    mut messages: EventReader<ShowMessage>,
+    mut banner_query: Query<(&mut Text, &mut TextColor, &mut MessageBanner)>,
+) {
+    let Ok((mut text, mut color, mut banner)) = banner_query.get_single_mut() else {
+        return;
+    };
+    if let Some(message) = messages.read().last() {
+        text.0 = message.text.clone();
+        banner.timer = message.duration;
     }
+    banner.timer = (banner.timer - time.delta_secs()).max(0.0);
+    color.0 = Color::srgba(1.0, 1.0, 1.0, banner.timer.min(1.0));
 }
@@ -0,0 +1,349 @@
+// src/waves.rs - Директор волн: волны врагов описываются в assets/waves/*.waves.ron
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::data::RonAssetLoader;
+use crate::archetypes::{EnemyArchetypes, EnemyCommandsExt};
+use crate::boss::BossArenaTrigger;
+use crate::perception::Perception;
+use crate::player::{Player, PlayerCamera};
+use crate::world::EnemySpawnPoint;
+use crate::level::{CurrentLevel, LevelDefinition};
+use crate::state::{GameState, InGame};
+
+pub struct WavesPlugin;
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<WaveSet>()
+           .register_asset_loader(RonAssetLoader::<WaveSet>::new(&["waves.ron"]))
+           .add_event::<WaveStarted>()
+           .add_event::<WaveCleared>()
+           .add_event::<StartWave>()
+           .add_systems(OnEnter(InGame), load_waves)
+           .add_systems(Update, (
+               start_requested_waves,
+               run_wave_director,
+               log_wave_events,
+           ).chain().run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Враги не появляются ближе этого расстояния к игроку
+const MIN_SPAWN_DISTANCE: f32 = 8.0;
+
+/// Набор волн для уровня
+#[derive(Asset, TypePath, Deserialize)]
+pub struct WaveSet {
+    #[serde(default)]
+    pub initial_delay: f32,   // Пауза перед первой волной (сек)
+    pub break_duration: f32,  // Передышка между волнами (сек)
+    pub waves: Vec<WaveDefinition>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct WaveDefinition {
+    pub name: String,
+    pub groups: Vec<SpawnGroupDefinition>,
+}
+
+/// Группа одинаковых врагов внутри волны
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SpawnGroupDefinition {
+    pub enemy: String,
+    pub count: u32,
+    #[serde(default)]
+    pub delay: f32,     // Задержка от начала волны до первого врага группы
+    pub interval: f32,  // Пауза между врагами группы
+    /// Группа точек спавна (`EnemySpawnPoint::group`), `None` - любая
+    #[serde(default)]
+    pub spawn_group: Option<String>,
+}
+
+#[derive(Event)]
+pub struct WaveStarted {
+    pub wave: usize,
+    pub name: String,
+}
+
+#[derive(Event)]
+pub struct WaveCleared {
+    pub wave: usize,
+}
+
+/// Запустить волну по имени сразу, без паузы. Директор переходит на эту волну:
+/// еще не появившиеся враги текущей волны отменяются, дальше волны идут по порядку
+#[derive(Event)]
+pub struct StartWave {
+    pub name: String,
+}
+
+/// Враг, появившийся в составе волны
+#[derive(Component)]
+pub struct WaveMember {
+    pub wave: usize,
+}
+
+pub enum WavePhase {
+    Loading,
+    Break(Timer),
+    Spawning,
+    Finished,
+}
+
+struct ActiveGroup {
+    definition: SpawnGroupDefinition,
+    remaining: u32,
+    cooldown: f32,
+}
+
+#[derive(Resource)]
+pub struct WaveDirector {
+    waves: Handle<WaveSet>,
+    pub phase: WavePhase,
+    pub wave_index: usize,
+    groups: Vec<ActiveGroup>,
+    next_spawn_point: usize,
+}
+
+/// Прогресс директора волн для сохранения игры
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WaveProgress {
+    pub wave_index: usize,
+    pub phase: SavedWavePhase,
+    pub groups: Vec<SavedGroup>,
+    pub next_spawn_point: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum SavedWavePhase {
+    Loading,
+    Break { remaining: f32 },
+    Spawning,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SavedGroup {
+    pub definition: SpawnGroupDefinition,
+    pub remaining: u32,
+    pub cooldown: f32,
+}
+
+impl WaveDirector {
+    pub fn progress(&self) -> WaveProgress {
+        WaveProgress {
+            wave_index: self.wave_index,
+            phase: match &self.phase {
+                WavePhase::Loading => SavedWavePhase::Loading,
+                WavePhase::Break(timer) => SavedWavePhase::Break { remaining: timer.remaining_secs() },
+                WavePhase::Spawning => SavedWavePhase::Spawning,
+                WavePhase::Finished => SavedWavePhase::Finished,
+            },
+            groups: self.groups.iter().map(|group| SavedGroup {
+                definition: group.definition.clone(),
+                remaining: group.remaining,
+                cooldown: group.cooldown,
+            }).collect(),
+            next_spawn_point: self.next_spawn_point,
+        }
+    }
+
+    /// Начать волну `index` набора немедленно
+    fn begin_wave(&mut self, wave_set: &WaveSet, index: usize) -> WaveStarted {
+        let wave = &wave_set.waves[index];
+        self.wave_index = index;
+        self.groups = wave.groups.iter().map(|group| ActiveGroup {
+            definition: group.clone(),
+            remaining: group.count,
+            cooldown: group.delay,
+        }).collect();
+        self.phase = WavePhase::Spawning;
+        WaveStarted { wave: index, name: wave.name.clone() }
+    }
+
+    /// Продолжить волны с сохраненного места. Набор волн остается тем, что загружен для уровня
+    pub fn restore(&mut self, progress: WaveProgress) {
+        self.wave_index = progress.wave_index;
+        self.phase = match progress.phase {
+            SavedWavePhase::Loading => WavePhase::Loading,
+            SavedWavePhase::Break { remaining } => WavePhase::Break(Timer::from_seconds(remaining.max(0.0), TimerMode::Once)),
+            SavedWavePhase::Spawning => WavePhase::Spawning,
+            SavedWavePhase::Finished => WavePhase::Finished,
+        };
+        self.groups = progress.groups.into_iter().map(|group| ActiveGroup {
+            definition: group.definition,
+            remaining: group.remaining,
+            cooldown: group.cooldown,
+        }).collect();
+        self.next_spawn_point = progress.next_spawn_point;
+    }
+}
+
+/// Каждый новый уровень начинает волны с начала - с тех, что указаны в файле уровня
+pub fn load_waves(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level: Res<CurrentLevel>,
+    levels: Res<Assets<LevelDefinition>>,
+) {
+    let waves = level.definition(&levels).map_or_else(Handle::default, |level| asset_server.load(&level.waves));
+    commands.insert_resource(WaveDirector {
+        waves,
+        phase: WavePhase::Loading,
+        wave_index: 0,
+        groups: Vec::new(),
+        next_spawn_point: 0,
+    });
+}
+
+/// Точка видна в кадре игровой камеры - спавнить там нельзя
+fn is_on_screen(camera: &Camera, camera_transform: &GlobalTransform, position: Vec3) -> bool {
+    // Небольшой запас за краем экрана, чтобы враг не высовывался наполовину
+    const MARGIN: f32 = 1.15;
+    camera.world_to_ndc(camera_transform, position).is_some_and(|ndc| {
+        ndc.x.abs() <= MARGIN && ndc.y.abs() <= MARGIN && ndc.z > 0.0 && ndc.z <= 1.0
+    })
+}
+
+fn run_wave_director(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut director: ResMut<WaveDirector>,
+    wave_sets: Res<Assets<WaveSet>>,
+    spawn_points: Query<(&GlobalTransform, &EnemySpawnPoint)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<PlayerCamera>>,
+    player_query: Query<&Transform, With<Player>>,
+    members: Query<&WaveMember>,
+    arenas: Query<(&Transform, &BossArenaTrigger)>,
+    archetypes: Res<EnemyArchetypes>,
+    mut wave_started: EventWriter<WaveStarted>,
+    mut wave_cleared: EventWriter<WaveCleared>,
+) {
+    let Some(wave_set) = wave_sets.get(&director.waves) else {
+        return;
+    };
+    let director = &mut *director;
+
+    match &mut director.phase {
+        WavePhase::Loading => {
+            // Ждем, пока загрузятся типы врагов, на которые ссылаются волны
+            if !archetypes.is_ready() {
+                return;
+            }
+            director.phase = WavePhase::Break(Timer::from_seconds(wave_set.initial_delay, TimerMode::Once));
+        }
+        WavePhase::Break(timer) => {
+            if !timer.tick(time.delta()).finished() {
+                return;
+            }
+            if director.wave_index >= wave_set.waves.len() {
+                director.phase = WavePhase::Finished;
+                info!("All waves cleared!");
+                return;
+            }
+            wave_started.send(director.begin_wave(wave_set, director.wave_index));
+        }
+        WavePhase::Spawning => {
+            let player_position = player_query.get_single().ok().map(|transform| transform.translation);
+            let camera = camera_query.get_single().ok();
+            let mut spawned_this_frame = false;
+
+            for group in director.groups.iter_mut() {
+                group.cooldown -= time.delta_secs();
+                while group.remaining > 0 && group.cooldown <= 0.0 {
+                    // Подходящие точки: нужной группы, вне экрана, не вплотную к игроку
+                    // и не за стенами запертой арены босса
+                    let candidates: Vec<Vec3> = spawn_points
+                        .iter()
+                        .filter(|(_, point)| group.definition.spawn_group.as_ref().is_none_or(|name| *name == point.group))
+                        .map(|(transform, _)| transform.translation())
+                        .filter(|position| camera.is_none_or(|(camera, camera_transform)| {
+                            !is_on_screen(camera, camera_transform, *position)
+                        }))
+                        .filter(|position| player_position.is_none_or(|player| {
+                            player.distance(*position) >= MIN_SPAWN_DISTANCE
+                        }))
+                        .filter(|position| !arenas.iter().any(|(transform, arena)| {
+                            arena.locks_in(transform.translation, *position)
+                        }))
+                        .collect();
+
+                    if candidates.is_empty() {
+                        // Все точки на виду - пробуем в следующем кадре
+                        break;
+                    }
+                    if !archetypes.contains(&group.definition.enemy) {
+                        warn!("Wave {}: unknown enemy type '{}', group skipped", director.wave_index + 1, group.definition.enemy);
+                        group.remaining = 0;
+                        break;
+                    }
+                    let position = candidates[director.next_spawn_point % candidates.len()];
+                    director.next_spawn_point += 1;
+
+                    debug!("Wave {}: spawning {} at {:?}", director.wave_index + 1, group.definition.enemy, position);
+                    let wave = director.wave_index;
+                    commands
+                        .spawn_enemy_archetype(&group.definition.enemy, position)
+                        .insert(WaveMember { wave })
+                        .queue(move |mut enemy: EntityWorldMut| {
+                            // Враги волны сразу идут туда, где игрок был в момент их появления
+                            if let Some(mut perception) = enemy.get_mut::<Perception>() {
+                                perception.last_known_position = player_position;
+                            }
+                        });
+
+                    group.remaining -= 1;
+                    group.cooldown += group.definition.interval;
+                    spawned_this_frame = true;
+                }
+            }
+
+            // WaveMember только что заспавненных врагов появится в запросе лишь в следующем кадре
+            let all_spawned = director.groups.iter().all(|group| group.remaining == 0);
+            let wave_alive = spawned_this_frame
+                || members.iter().any(|member| member.wave == director.wave_index);
+            if all_spawned && !wave_alive {
+                wave_cleared.send(WaveCleared { wave: director.wave_index });
+                director.wave_index += 1;
+                director.groups.clear();
+                director.phase = WavePhase::Break(Timer::from_seconds(wave_set.break_duration, TimerMode::Once));
+            }
+        }
+        WavePhase::Finished => {}
+    }
+}
+
+fn start_requested_waves(
+    mut requests: EventReader<StartWave>,
+    mut director: ResMut<WaveDirector>,
+    wave_sets: Res<Assets<WaveSet>>,
+    mut wave_started: EventWriter<WaveStarted>,
+) {
+    let Some(wave_set) = wave_sets.get(&director.waves) else {
+        // Набор волн еще грузится - запрос пропадет, поэтому предупреждаем
+        for request in requests.read() {
+            warn!("Wave '{}' requested before the wave set was loaded", request.name);
+        }
+        return;
+    };
+    for request in requests.read() {
+        match wave_set.waves.iter().position(|wave| wave.name == request.name) {
+            Some(index) => {
+                let started = director.begin_wave(wave_set, index);
+                wave_started.send(started);
+            }
+            None => warn!("Unknown wave '{}'", request.name),
+        }
+    }
+}
+
+fn log_wave_events(
+    mut wave_started: EventReader<WaveStarted>,
+    mut wave_cleared: EventReader<WaveCleared>,
+) {
+    for event in wave_started.read() {
+        info!("Wave {} started: {}", event.wave + 1, event.name);
+    }
+    for event in wave_cleared.read() {
+        info!("Wave {} cleared!", event.wave + 1);
+    }
+}
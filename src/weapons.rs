@@ -1,124 +1,330 @@
-// src/weapons.rs - Стрельба вперед по направлению игрока
+// src/weapons.rs - Стрельба для любых сущностей с оружием: игрока и врагов-стрелков
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::player::Player;
+use serde::{Deserialize, Serialize};
+use crate::player::{Control, KeyBindings, Player};
+use crate::perception::NoiseEvent;
+use crate::enemies::{Health, Hit};
+use crate::stats::{Stat, Stats};
+use crate::state::{GameState, InGame};
 
 pub struct WeaponsPlugin;
 impl Plugin for WeaponsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (
-               handle_shooting,
+        app.add_event::<WeaponFired>()
+           .add_event::<BulletHit>()
+           .add_systems(Update, (
+               handle_shooting.before(fire_weapons),
+               fire_weapons,
+               bullet_hit_system,
                update_bullets,
                cleanup_bullets,
-           ));
+           ).run_if(in_state(GameState::Playing)));
     }
 }
 
-#[derive(Component)]
+/// Сторона конфликта: пули не ранят своих
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Faction {
+    Player,
+    Enemy,
+    /// Разрушаемые предметы: их бьют обе стороны
+    Neutral,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Weapon {
+    pub name: String,
     pub damage: f32,
     pub fire_rate: f32,      // выстрелов в секунду
+    #[serde(skip)]           // Время по часам прошлой сессии - после загрузки можно стрелять сразу
     pub last_shot_time: f32,
+    pub noise_radius: f32,   // на каком расстоянии враги слышат выстрел (0 - бесшумно)
+    pub projectile_speed: f32,
+    pub pellets: u32,        // пуль за выстрел
+    pub spread: f32,         // ширина веера пуль (радианы)
+    pub ammo: Option<u32>,   // None - бесконечные патроны
+    pub max_ammo: u32,
 }
 
 impl Default for Weapon {
     fn default() -> Self {
         Self {
+            name: "blaster".to_string(),
             damage: 25.0,
             fire_rate: 10.0,     // 10 выстрелов в секунду
             last_shot_time: 0.0,
+            noise_radius: 20.0,
+            projectile_speed: 50.0,
+            pellets: 1,
+            spread: 0.0,
+            ammo: Some(150),
+            max_ammo: 300,
+        }
+    }
+}
+
+/// Оружие из данных - например, в подбираемом предмете
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeaponDefinition {
+    pub name: String,
+    pub damage: f32,
+    pub fire_rate: f32,
+    pub projectile_speed: f32,
+    #[serde(default = "default_pellets")]
+    pub pellets: u32,
+    #[serde(default)]
+    pub spread_degrees: f32,
+    #[serde(default = "default_noise_radius")]
+    pub noise_radius: f32,
+    pub ammo: u32,
+    pub max_ammo: u32,
+}
+
+fn default_pellets() -> u32 {
+    1
+}
+
+fn default_noise_radius() -> f32 {
+    20.0
+}
+
+impl WeaponDefinition {
+    pub fn to_weapon(&self) -> Weapon {
+        Weapon {
+            name: self.name.clone(),
+            damage: self.damage,
+            fire_rate: self.fire_rate,
+            last_shot_time: 0.0,
+            noise_radius: self.noise_radius,
+            projectile_speed: self.projectile_speed,
+            pellets: self.pellets.max(1),
+            spread: self.spread_degrees.to_radians(),
+            ammo: Some(self.ammo.min(self.max_ammo)),
+            max_ammo: self.max_ammo,
         }
     }
 }
 
+impl Weapon {
+    /// Точка вылета пули - немного перед стрелком
+    pub fn muzzle_position(transform: &Transform, direction: Vec3) -> Vec3 {
+        let horizontal = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+        transform.translation + horizontal * 1.0 + Vec3::Y * 0.5
+    }
+}
+
+/// Куда целится владелец оружия и жмет ли он на спуск.
+/// Игрок управляет им с мыши, враги - из ИИ
+#[derive(Component, Default)]
+pub struct WeaponAim {
+    pub direction: Vec3,
+    pub trigger: bool,
+}
+
 #[derive(Component)]
 pub struct Bullet {
-    //pub velocity: Vec3,
     pub damage: f32,
     pub lifetime: f32,
+    pub faction: Faction,
+    pub shooter: Option<Entity>,
+}
+
+/// Выстрел: сколько пуль вылетело за одно нажатие
+#[derive(Event)]
+pub struct WeaponFired {
+    pub shooter: Entity,
+    pub projectiles: u32,
 }
 
+/// Пуля попала в сущность со здоровьем
+#[derive(Event)]
+pub struct BulletHit {
+    pub shooter: Option<Entity>,
+    pub target: Entity,
+}
+
+// Толчок от пули на единицу урона (м/с)
+const BULLET_IMPULSE_PER_DAMAGE: f32 = 0.2;
+
 pub fn handle_shooting(
-    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
     mouse_input: Res<ButtonInput<MouseButton>>,
-    mut player_query: Query<(&Transform, &mut Weapon), With<Player>>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    bindings: Res<KeyBindings>,
+    mut player_query: Query<(&Transform, &mut WeaponAim), With<Player>>,
 ) {
-    let Ok((player_transform, mut weapon)) = player_query.get_single_mut() else {
+    let Ok((player_transform, mut aim)) = player_query.get_single_mut() else {
         return;
     };
 
-    // Проверяем, можем ли стрелять (cooldown)
+    // Стреляем в направлении "вперед" игрока
+    // В Bevy forward направление это -Z (Vec3::NEG_Z)
+    aim.direction = player_transform.rotation * Vec3::NEG_Z;
+    aim.trigger = bindings.pressed(Control::Fire, &keyboard, &mouse_input);
+}
+
+pub fn fire_weapons(
+    time: Res<Time>,
+    mut shooters: Query<(Entity, &Transform, &mut Weapon, &WeaponAim, &Faction, Option<&Stats>)>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut noise_events: EventWriter<NoiseEvent>,
+    mut fired: EventWriter<WeaponFired>,
+) {
     let current_time = time.elapsed_secs();
-    let time_since_last_shot = current_time - weapon.last_shot_time;
-    let can_shoot = time_since_last_shot >= (1.0 / weapon.fire_rate);
-
-    if mouse_input.pressed(MouseButton::Left) && can_shoot {
-        // Стреляем в направлении "вперед" игрока
-        // В Bevy forward направление это -Z (Vec3::NEG_Z)
-        let forward_direction = player_transform.rotation * Vec3::NEG_Z;
-        
-        // Позиция спавна пули - немного перед игроком
-        let spawn_position = player_transform.translation + forward_direction * 1.0 + Vec3::Y * 0.5;
-        
-        // Создаем пулю
-        spawn_bullet(
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-            spawn_position,
-            forward_direction,
-            weapon.damage,
-        );
-        
+
+    for (entity, transform, mut weapon, aim, faction, stats) in shooters.iter_mut() {
+        let damage = stats.map_or(weapon.damage, |stats| stats.apply(Stat::Damage, weapon.damage));
+        let fire_rate = stats.map_or(weapon.fire_rate, |stats| stats.apply(Stat::FireRate, weapon.fire_rate));
+
+        // Проверяем, можем ли стрелять (cooldown)
+        let time_since_last_shot = current_time - weapon.last_shot_time;
+        let can_shoot = time_since_last_shot >= (1.0 / fire_rate);
+        let direction = aim.direction.normalize_or_zero();
+
+        let has_ammo = weapon.ammo.is_none_or(|ammo| ammo > 0);
+
+        if !aim.trigger || !can_shoot || !has_ammo || direction == Vec3::ZERO {
+            continue;
+        }
+
+        // Несколько пуль расходятся веером в горизонтальной плоскости
+        for pellet in 0..weapon.pellets {
+            let offset = if weapon.pellets > 1 {
+                weapon.spread * (pellet as f32 / (weapon.pellets - 1) as f32 - 0.5)
+            } else {
+                0.0
+            };
+            let pellet_direction = Quat::from_rotation_y(offset) * direction;
+            spawn_bullet(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                Weapon::muzzle_position(transform, direction),
+                pellet_direction * weapon.projectile_speed,
+                damage,
+                *faction,
+                Some(entity),
+            );
+        }
+        if let Some(ammo) = weapon.ammo.as_mut() {
+            *ammo -= 1;
+        }
+        fired.send(WeaponFired {
+            shooter: entity,
+            projectiles: weapon.pellets,
+        });
+
+        // Выстрел слышен врагам поблизости
+        if weapon.noise_radius > 0.0 {
+            noise_events.send(NoiseEvent {
+                source: entity,
+                position: transform.translation,
+                radius: weapon.noise_radius,
+            });
+        }
+
         weapon.last_shot_time = current_time;
     }
 }
 
-fn spawn_bullet(
+pub fn spawn_bullet(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     position: Vec3,
-    direction: Vec3,
+    velocity: Vec3,
     damage: f32,
+    faction: Faction,
+    shooter: Option<Entity>,
 ) {
-    let bullet_speed = 50.0;
-    
+    // Вражеские пули крупнее и другого цвета, чтобы от них было проще уворачиваться
+    let (radius, color) = match faction {
+        Faction::Player => (0.15, LinearRgba::rgb(1.0, 1.0, 0.0)),  // Желтые пули
+        Faction::Enemy | Faction::Neutral => (0.22, LinearRgba::rgb(1.0, 0.1, 0.8)),
+    };
+
     commands.spawn((
-        Mesh3d(meshes.add(Sphere::new(0.15))), // Чуть больше пули
+        Mesh3d(meshes.add(Sphere::new(radius))),
         MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(1.0, 1.0, 0.0), // Желтые пули
-            emissive: LinearRgba::rgb(1.0, 1.0, 0.0),
+            base_color: color.into(),
+            emissive: color,
             ..default()
         })),
         Transform::from_translation(position),
         Bullet {
-            //velocity: direction * bullet_speed,
             damage,
             lifetime: 2.0, // пуля живет 2 секунды
+            faction,
+            shooter,
         },
         RigidBody::Dynamic,
-        Collider::ball(0.15),
+        GravityScale(0.0),
+        Collider::ball(radius),
         Sensor, // пуля проходит сквозь объекты, но регистрирует столкновения
+        // Без этого Rapier не присылает CollisionEvent для пули
+        ActiveEvents::COLLISION_EVENTS,
         Velocity {
-            linvel: direction * bullet_speed,
+            linvel: velocity,
             angvel: Vec3::ZERO,
         },
         Name::new("Bullet"),
+        StateScoped(InGame),
     ));
 }
 
+/// Попадание пули в любую сущность со здоровьем из чужой фракции
+fn bullet_hit_system(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    bullet_query: Query<(&Bullet, &Velocity)>,
+    mut target_query: Query<(&mut Health, &Faction)>,
+    mut hits: EventWriter<BulletHit>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = collision_event else {
+            continue;
+        };
+        // Проверяем, столкнулась ли пуля с целью
+        let (bullet_entity, target_entity) = if bullet_query.contains(*entity1) {
+            (*entity1, *entity2)
+        } else if bullet_query.contains(*entity2) {
+            (*entity2, *entity1)
+        } else {
+            continue;
+        };
+
+        let Ok((bullet, velocity)) = bullet_query.get(bullet_entity) else {
+            continue;
+        };
+        let Ok((mut health, faction)) = target_query.get_mut(target_entity) else {
+            continue;
+        };
+        // Свои пули пролетают насквозь
+        if *faction == bullet.faction {
+            continue;
+        }
+
+        health.take_damage(bullet.damage, Hit {
+            source: bullet.shooter,
+            impulse: velocity.linvel.normalize_or_zero() * bullet.damage * BULLET_IMPULSE_PER_DAMAGE,
+        });
+        hits.send(BulletHit {
+            shooter: bullet.shooter,
+            target: target_entity,
+        });
+        // Удаляем пулю
+        commands.entity(bullet_entity).despawn();
+    }
+}
+
 fn update_bullets(
     time: Res<Time>,
     mut bullet_query: Query<(Entity, &mut Bullet)>,
     mut commands: Commands,
 ) {
     for (entity, mut bullet) in bullet_query.iter_mut() {
-        // Двигаем пулю
-        //transform.translation += bullet.velocity * time.delta_secs();
         // Уменьшаем время жизни
         bullet.lifetime -= time.delta_secs();
         if bullet.lifetime <= 0.0 {
@@ -144,6 +350,6 @@ pub fn equip_player_weapon(
     player_query: Query<Entity, (With<Player>, Without<Weapon>)>,
 ) {
     for player_entity in player_query.iter() {
-        commands.entity(player_entity).insert(Weapon::default());
+        commands.entity(player_entity).insert((Weapon::default(), WeaponAim::default()));
     }
 }
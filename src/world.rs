@@ -1,50 +1,289 @@
-// src/world.rs - Восстановленная версия с поворотом игрока к курсору
+// src/world.rs - Восстановленная версия с поворотом игрока к курсору.
+// Сама геометрия уровня строится из файла уровня (level.rs), здесь - ее интерактивные части:
+// зоны смерти, прыжковые площадки, телепорты и ускорители
+use bevy::ecs::query::QueryData;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::player::{Player, WishDirection, PlayerCamera};
+use serde::Deserialize;
+use crate::enemies::{Enemy, Health, Hit};
+use crate::player::{self, apply_acceleration_cpma, move_kinematic_player_by_velocity, GameSettings, Player, PlayerCamera, WishDirection};
+use crate::state::GameState;
+use crate::weapons::Bullet;
 
 pub struct WorldPlugin;
 impl Plugin for WorldPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, (spawn_floor, spawn_collision_cube))
-           .add_systems(Update, draw_cursor);
+        app.add_systems(Update, (
+               draw_cursor,
+               apply_kill_volumes,
+           ).run_if(in_state(GameState::Playing)))
+           // Между расчетом скорости и движением: иначе на земле горизонтальную скорость
+           // запуска срежет ограничение sv_maxspeed
+           .add_systems(FixedUpdate, (
+               tick_movement_timers,
+               apply_jump_pads,
+               apply_teleporters,
+               apply_boost_strips,
+           ).chain()
+               .after(apply_acceleration_cpma)
+               .before(move_kinematic_player_by_velocity)
+               .run_if(in_state(GameState::Playing)));
     }
 }
 
+// Насколько вершина дуги прыжковой площадки выше старта и цели
+const JUMP_PAD_ARC: f32 = 2.0;
+// Сколько после ускорителя скорость не режется до обычной (сек)
+const BOOST_HOLD_TIME: f32 = 0.75;
+// Пауза после телепорта, чтобы не улететь обратно через парный телепорт (сек)
+const TELEPORT_COOLDOWN: f32 = 0.5;
+
 #[derive(Component)]
 pub struct Ground;
 
-fn spawn_floor(
+/// Точка появления врагов; волны могут ссылаться на группу точек по имени
+#[derive(Component)]
+pub struct EnemySpawnPoint {
+    pub group: String,
+}
+
+/// Сенсор, убивающий все живое внутри (пропасть, лава без шансов)
+#[derive(Component)]
+pub struct KillVolume;
+
+fn apply_kill_volumes(
+    rapier_context: ReadRapierContext,
+    volumes: Query<Entity, With<KillVolume>>,
+    mut victims: Query<&mut Health>,
+) {
+    let context = rapier_context.single();
+    for volume in volumes.iter() {
+        for (first, second, intersecting) in context.intersection_pairs_with(volume) {
+            let other = if first == volume { second } else { first };
+            let Ok(mut health) = victims.get_mut(other) else {
+                continue;
+            };
+            if intersecting && !health.is_dead() {
+                health.take_damage(f32::MAX, Hit { source: None, impulse: Vec3::ZERO });
+            }
+        }
+    }
+}
+
+/// Кого пропускают площадки, телепорты и ускорители; по умолчанию только игрока
+#[derive(Component, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Affects {
+    pub player: bool,
+    pub enemies: bool,
+    pub projectiles: bool,
+}
+
+impl Default for Affects {
+    fn default() -> Self {
+        Self { player: true, enemies: false, projectiles: false }
+    }
+}
+
+/// Прыжковая площадка: бросает по дуге точно в `target`.
+/// Пули летят без гравитации - их площадка просто разворачивает на цель
+#[derive(Component)]
+pub struct JumpPad {
+    pub target: Vec3,
+}
+
+/// Телепорт: переносит в `destination`. С `facing` (радианы вокруг вертикали) горизонтальная
+/// скорость поворачивается в сторону выхода, без `keep_velocity` - гасится.
+/// Точка выхода не должна лежать внутри другого телепорта
+#[derive(Component)]
+pub struct Teleporter {
+    pub destination: Vec3,
+    pub facing: Option<f32>,
+    pub keep_velocity: bool,
+}
+
+/// Ускоритель: поднимает скорость вдоль `direction` хотя бы до `speed`
+#[derive(Component)]
+pub struct BoostStrip {
+    pub direction: Vec3,
+    pub speed: f32,
+}
+
+/// Тело летит по скорости, которую ему задал уровень: игроку на земле не режется скорость,
+/// враг не управляет собой, пока не выйдет время
+#[derive(Component)]
+pub struct Launched {
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+struct TeleportCooldown(f32);
+
+/// Все, что могут подбросить, перенести или разогнать
+#[derive(QueryData)]
+#[query_data(mutable)]
+struct MovableBody {
+    transform: &'static mut Transform,
+    player_velocity: Option<&'static mut player::Velocity>,
+    velocity: Option<&'static mut Velocity>,
+    player: Has<Player>,
+    enemy: Has<Enemy>,
+    projectile: Has<Bullet>,
+    teleport_cooldown: Has<TeleportCooldown>,
+}
+
+impl MovableBodyItem<'_> {
+    fn velocity(&self) -> Vec3 {
+        match (&self.player_velocity, &self.velocity) {
+            (Some(velocity), _) => velocity.0,
+            (None, Some(velocity)) => velocity.linvel,
+            (None, None) => Vec3::ZERO,
+        }
+    }
+
+    fn set_velocity(&mut self, value: Vec3) {
+        if let Some(velocity) = self.player_velocity.as_mut() {
+            velocity.0 = value;
+        } else if let Some(velocity) = self.velocity.as_mut() {
+            velocity.linvel = value;
+        }
+    }
+
+    fn is_affected(&self, affects: &Affects) -> bool {
+        (self.player && affects.player) || (self.enemy && affects.enemies) || (self.projectile && affects.projectiles)
+    }
+}
+
+/// Начальная скорость и время полета, с которыми тело под гравитацией `gravity` (отрицательной)
+/// попадет из `from` в `to`, поднявшись на `arc` над более высокой из точек
+pub fn launch_velocity(from: Vec3, to: Vec3, gravity: f32, arc: f32) -> (Vec3, f32) {
+    let gravity = gravity.abs();
+    let apex = from.y.max(to.y) + arc;
+    let rise = (2.0 * gravity * (apex - from.y)).sqrt();
+    let flight_time = rise / gravity + (2.0 * (apex - to.y) / gravity).sqrt();
+    let horizontal = Vec3::new(to.x - from.x, 0.0, to.z - from.z) / flight_time;
+    (horizontal + Vec3::Y * rise, flight_time)
+}
+
+/// Что сейчас внутри сенсора
+pub fn bodies_inside(context: &RapierContext, volume: Entity) -> Vec<Entity> {
+    context
+        .intersection_pairs_with(volume)
+        .filter(|(_, _, intersecting)| *intersecting)
+        .map(|(first, second, _)| if first == volume { second } else { first })
+        .collect()
+}
+
+fn tick_movement_timers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut launched: Query<(Entity, &mut Launched)>,
+    mut cooldowns: Query<(Entity, &mut TeleportCooldown)>,
+) {
+    for (entity, mut launched) in launched.iter_mut() {
+        launched.remaining -= time.delta_secs();
+        if launched.remaining <= 0.0 {
+            commands.entity(entity).remove::<Launched>();
+        }
+    }
+    for (entity, mut cooldown) in cooldowns.iter_mut() {
+        cooldown.0 -= time.delta_secs();
+        if cooldown.0 <= 0.0 {
+            commands.entity(entity).remove::<TeleportCooldown>();
+        }
+    }
+}
+
+fn apply_jump_pads(
+    mut commands: Commands,
+    rapier_context: ReadRapierContext,
+    settings: Res<GameSettings>,
+    pads: Query<(Entity, &JumpPad, &Affects)>,
+    mut bodies: Query<MovableBody>,
+) {
+    let context = rapier_context.single();
+    for (pad_entity, pad, affects) in pads.iter() {
+        for entity in bodies_inside(&context, pad_entity) {
+            let Ok(mut body) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if !body.is_affected(affects) {
+                continue;
+            }
+            let position = body.transform.translation;
+            if body.projectile {
+                let speed = body.velocity().length();
+                body.set_velocity((pad.target - position).normalize_or_zero() * speed);
+                continue;
+            }
+            let (velocity, flight_time) = launch_velocity(position, pad.target, settings.sv_gravity, JUMP_PAD_ARC);
+            body.set_velocity(velocity);
+            commands.entity(entity).insert(Launched { remaining: flight_time });
+        }
+    }
+}
+
+fn apply_teleporters(
+    mut commands: Commands,
+    rapier_context: ReadRapierContext,
+    teleporters: Query<(Entity, &Teleporter, &Affects)>,
+    mut bodies: Query<MovableBody>,
+) {
+    let context = rapier_context.single();
+    for (teleporter_entity, teleporter, affects) in teleporters.iter() {
+        for entity in bodies_inside(&context, teleporter_entity) {
+            let Ok(mut body) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if !body.is_affected(affects) || body.teleport_cooldown {
+                continue;
+            }
+            let velocity = body.velocity();
+            let velocity = match teleporter.facing {
+                _ if !teleporter.keep_velocity => Vec3::ZERO,
+                Some(facing) => {
+                    // Вертикальная скорость не меняется, горизонтальная уходит в сторону выхода
+                    let forward = Quat::from_rotation_y(facing) * Vec3::NEG_Z;
+                    forward * Vec3::new(velocity.x, 0.0, velocity.z).length() + Vec3::Y * velocity.y
+                }
+                None => velocity,
+            };
+            body.set_velocity(velocity);
+            body.transform.translation = teleporter.destination;
+            // Игрок сам смотрит на курсор, остальных разворачиваем к выходу
+            if let (Some(facing), false) = (teleporter.facing, body.player) {
+                body.transform.rotation = Quat::from_rotation_y(facing);
+            }
+            commands.entity(entity).insert(TeleportCooldown(TELEPORT_COOLDOWN));
+        }
+    }
+}
+
+fn apply_boost_strips(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    rapier_context: ReadRapierContext,
+    strips: Query<(Entity, &BoostStrip, &Affects)>,
+    mut bodies: Query<MovableBody>,
 ) {
-    let entity = commands.spawn((
-        Mesh3d(meshes.add(Plane3d::default().mesh().size(50.0, 50.0))), // Увеличили арену
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: Color::srgb(0.5, 0.5, 0.5),
-            perceptual_roughness: 0.9,
-            metallic: 0.0,
-            ..default()
-        })),
-        Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
-        Ground,
-    )).id();
-    
-    // Добавляем физическую коллизию для земли
-    commands.entity(entity).insert((
-        RigidBody::Fixed,
-        // Коллайдер пола - тонкий прямоугольник
-        Collider::cuboid(25.0, 0.1, 25.0), // width, height/2, depth
-        Friction {
-            coefficient: 0.0, // Убираем трение для Quake-физики
-            combine_rule: CoefficientCombineRule::Min,
-        },
-        Restitution {
-            coefficient: 0.0, // Никакого отскока
-            combine_rule: CoefficientCombineRule::Min,
-        },
-    ));
+    let context = rapier_context.single();
+    for (strip_entity, strip, affects) in strips.iter() {
+        for entity in bodies_inside(&context, strip_entity) {
+            let Ok(mut body) = bodies.get_mut(entity) else {
+                continue;
+            };
+            if !body.is_affected(affects) {
+                continue;
+            }
+            let velocity = body.velocity();
+            let along = velocity.dot(strip.direction);
+            if along < strip.speed {
+                body.set_velocity(velocity + strip.direction * (strip.speed - along));
+            }
+            if !body.projectile {
+                commands.entity(entity).insert(Launched { remaining: BOOST_HOLD_TIME });
+            }
+        }
+    }
 }
 
 pub fn draw_cursor(
@@ -116,33 +355,55 @@ pub fn draw_cursor(
     }
 }
 
-pub fn spawn_collision_cube(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    // Добавляем несколько препятствий на арене
-    let obstacles = vec![
-        Vec3::new(8.0, 1.0, 8.0),
-        Vec3::new(-8.0, 1.0, 8.0),
-        Vec3::new(8.0, 1.0, -8.0),
-        Vec3::new(-8.0, 1.0, -8.0),
-        Vec3::new(0.0, 1.0, 10.0),
-        Vec3::new(0.0, 1.0, -10.0),
-    ];
-    
-    for pos in obstacles {
-        commands.spawn((
-            Mesh3d(meshes.add(Cuboid::new(2.0, 2.0, 2.0).mesh())),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::srgb(0.2, 0.3, 0.8),
-                perceptual_roughness: 0.5,
-                metallic: 0.5,
-                ..default()
-            })),
-            Transform::from_translation(pos),
-            RigidBody::Fixed,
-            Collider::cuboid(1.0, 1.0, 1.0),
-        ));
-    }
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Положение тела под гравитацией через `t` секунд после старта
+    fn position_at(from: Vec3, velocity: Vec3, gravity: f32, t: f32) -> Vec3 {
+        from + velocity * t + Vec3::Y * (0.5 * gravity * t * t)
+    }
+
+    #[test]
+    fn launch_lands_on_target() {
+        let gravity = -9.81;
+        let cases = [
+            (Vec3::ZERO, Vec3::new(10.0, 0.0, 0.0)),
+            (Vec3::new(0.0, 1.0, 0.0), Vec3::new(-4.0, 6.0, 8.0)),
+            (Vec3::new(5.0, 8.0, 5.0), Vec3::new(0.0, 0.0, -12.0)),
+        ];
+        for (from, to) in cases {
+            let (velocity, flight_time) = launch_velocity(from, to, gravity, 2.0);
+            let landing = position_at(from, velocity, gravity, flight_time);
+            assert!(landing.distance(to) < 1e-3, "{:?} -> {:?} landed at {:?}", from, to, landing);
+        }
+    }
+
+    #[test]
+    fn launch_peaks_at_arc_above_higher_point() {
+        let gravity = -20.0;
+        let (from, to, arc) = (Vec3::ZERO, Vec3::new(6.0, 3.0, 0.0), 1.5);
+        let (velocity, _) = launch_velocity(from, to, gravity, arc);
+        // Вершина параболы - там, где вертикальная скорость обнуляется
+        let apex_time = velocity.y / -gravity;
+        let apex = position_at(from, velocity, gravity, apex_time);
+        assert!((apex.y - (to.y + arc)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn launch_ignores_gravity_sign() {
+        let (from, to) = (Vec3::ZERO, Vec3::new(3.0, 0.0, 4.0));
+        let (down, down_time) = launch_velocity(from, to, -9.81, 1.0);
+        let (up, up_time) = launch_velocity(from, to, 9.81, 1.0);
+        assert_eq!(down, up);
+        assert_eq!(down_time, up_time);
+    }
+
+    #[test]
+    fn vertical_launch_has_no_horizontal_speed() {
+        let (velocity, flight_time) = launch_velocity(Vec3::ZERO, Vec3::new(0.0, 4.0, 0.0), -9.81, 1.0);
+        assert_eq!(velocity.x, 0.0);
+        assert_eq!(velocity.z, 0.0);
+        assert!(velocity.y > 0.0 && flight_time > 0.0);
+    }
+}